@@ -20,6 +20,41 @@ pub const MAX_OUTPUT_SIZE: usize = 100 * 1024; // 100KB
 /// 2,000 lines
 pub const MAX_READ_LINES: usize = 2000;
 
+/// Maximum file size in bytes that read helpers will load
+///
+/// Files larger than this are refused rather than read, so a stray core dump
+/// or database file can't exhaust memory. See [`check_file_size`](crate::check_file_size).
+///
+/// # Value
+/// 10 MiB (10,485,760 bytes)
+pub const MAX_FILE_READ_BYTES: u64 = 10 * 1024 * 1024; // 10 MiB
+
+/// Maximum number of matches returned by glob expansion
+///
+/// Patterns matching more paths than this fail instead of returning a huge list.
+///
+/// # Value
+/// 1,000 paths
+pub const MAX_GLOB_RESULTS: usize = 1000;
+
+/// Maximum number of entries returned by a directory listing
+///
+/// Listings with more entries than this are cut off and marked as truncated.
+///
+/// # Value
+/// 1,000 entries
+pub const MAX_LIST_ENTRIES: usize = 1000;
+
+/// Maximum number of symlinks followed while resolving one path
+///
+/// Resolution that needs more links than this fails with
+/// [`ToolError::SymlinkLoop`](crate::ToolError::SymlinkLoop) instead of a
+/// generic I/O error. Matches the Linux kernel's limit.
+///
+/// # Value
+/// 40 links
+pub const MAX_SYMLINK_DEPTH: usize = 40;
+
 /// Maximum line length before truncation
 ///
 /// Lines longer than this will be truncated with "..." appended.
@@ -36,6 +36,25 @@ pub enum ToolError {
     #[error("Path '{0}' is outside workspace")]
     PathOutsideWorkspace(String),
 
+    /// Path is outside every allowed root
+    ///
+    /// Returned by a [`Sandbox`](crate::Sandbox) configured with roots beyond
+    /// the workspace. Lists every root that was consulted.
+    #[error("Path '{path}' is outside allowed roots: {}", roots.join(", "))]
+    PathOutsideRoots {
+        /// The rejected path
+        path: String,
+        /// The roots that were consulted, workspace first
+        roots: Vec<String>,
+    },
+
+    /// Multiple paths failed
+    ///
+    /// Returned by batch operations that require every path to succeed.
+    /// Carries each failing path paired with the reason it failed.
+    #[error("{}", format_path_failures(.0))]
+    PathsFailed(Vec<(String, String)>),
+
     /// Invalid argument
     ///
     /// Returned when a tool receives an argument with an invalid value.
@@ -65,12 +84,76 @@ pub enum ToolError {
     #[error("Command failed: {0}")]
     CommandFailed(String),
 
+    /// Size limit exceeded
+    ///
+    /// Returned when a file, output, or other quantity exceeds a configured limit.
+    #[error("{what} limit exceeded: {} > {}", crate::output::format_count(*.actual), crate::output::format_count(*.limit))]
+    LimitExceeded {
+        /// What was being limited (e.g. "output size")
+        what: String,
+        /// The configured limit
+        limit: usize,
+        /// The actual value that exceeded the limit
+        actual: usize,
+    },
+
+    /// Binary content
+    ///
+    /// Returned when content looks binary and can't be meaningfully shown as text.
+    #[error("Binary content ({} bytes) cannot be displayed", crate::output::format_count(*.size))]
+    BinaryContent {
+        /// Size of the content in bytes
+        size: usize,
+    },
+
+    /// File too large
+    ///
+    /// Returned when a file exceeds the size a helper is willing to read.
+    #[error("File '{path}' is too large: {} exceeds limit of {}", crate::output::format_bytes(*.size), crate::output::format_bytes(*.max))]
+    FileTooLarge {
+        /// The offending file
+        path: String,
+        /// The file's size in bytes
+        size: u64,
+        /// The maximum allowed size in bytes
+        max: u64,
+    },
+
+    /// Symlink loop
+    ///
+    /// Returned when resolving a path follows more than
+    /// [`MAX_SYMLINK_DEPTH`](crate::MAX_SYMLINK_DEPTH) symlinks, e.g. `a -> b`, `b -> a`.
+    #[error("Symlink loop detected while resolving '{0}'")]
+    SymlinkLoop(String),
+
+    /// Symlink denied
+    ///
+    /// Returned in strict mode ([`ResolveOptions::follow_symlinks`](crate::ResolveOptions::follow_symlinks)
+    /// set to `false`) when any component of the path is a symlink, even one
+    /// pointing inside the workspace.
+    #[error("Path '{0}' contains a symlink, which is not allowed")]
+    SymlinkDenied(String),
+
     /// Timeout
     ///
     /// Returned when an operation exceeds its timeout limit.
     #[error("Operation timed out after {0}ms")]
     Timeout(u64),
 
+    /// Command timeout
+    ///
+    /// Returned when a command is killed for exceeding its timeout. Carries
+    /// whatever output was captured before the kill, already size-capped.
+    #[error("Command timed out after {timeout_ms}ms")]
+    CommandTimedOut {
+        /// The timeout that expired, in milliseconds
+        timeout_ms: u64,
+        /// Standard output captured before the kill
+        stdout: String,
+        /// Standard error captured before the kill
+        stderr: String,
+    },
+
     /// Other error
     ///
     /// A catch-all for errors that don't fit other categories.
@@ -78,6 +161,31 @@ pub enum ToolError {
     Other(String),
 }
 
+/// Render `(path, reason)` pairs as a header plus one indented line per path
+fn format_path_failures(failures: &[(String, String)]) -> String {
+    let noun = if failures.len() == 1 { "path" } else { "paths" };
+    let mut message = format!("{} {} failed:", failures.len(), noun);
+    for (path, reason) in failures {
+        message.push_str(&format!("\n  {}: {}", path, reason));
+    }
+    message
+}
+
+/// Broad classification of a [`ToolError`]
+///
+/// Lets callers decide how to react without matching on every variant:
+/// user errors should be reported back so the request can be corrected,
+/// environment errors come from the host (filesystem, processes, time).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorCategory {
+    /// The request itself was invalid (bad argument, path, or size)
+    User,
+    /// The environment failed (I/O, command execution, timeout)
+    Environment,
+    /// Unclassified errors ([`ToolError::Other`])
+    Other,
+}
+
 impl ToolError {
     /// Create an invalid argument error
     ///
@@ -109,6 +217,490 @@ impl ToolError {
     pub fn missing_arg(name: impl Into<String>) -> Self {
         Self::MissingArgument(name.into())
     }
+
+    /// Create a limit exceeded error
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use a3s_tools_core::{ToolError, MAX_OUTPUT_SIZE};
+    ///
+    /// let err = ToolError::limit_exceeded("output size", MAX_OUTPUT_SIZE, 250_000);
+    /// assert_eq!(err.to_string(), "output size limit exceeded: 250,000 > 102,400");
+    /// ```
+    pub fn limit_exceeded(what: impl Into<String>, limit: usize, actual: usize) -> Self {
+        Self::LimitExceeded {
+            what: what.into(),
+            limit,
+            actual,
+        }
+    }
+
+    /// Classify this error
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use a3s_tools_core::{ErrorCategory, ToolError};
+    ///
+    /// assert_eq!(ToolError::missing_arg("path").category(), ErrorCategory::User);
+    /// assert_eq!(ToolError::Timeout(1000).category(), ErrorCategory::Environment);
+    /// ```
+    pub fn category(&self) -> ErrorCategory {
+        match self {
+            Self::PathNotFound(_)
+            | Self::PathOutsideWorkspace(_)
+            | Self::PathOutsideRoots { .. }
+            | Self::PathsFailed(_)
+            | Self::InvalidArgument { .. }
+            | Self::MissingArgument(_)
+            | Self::LimitExceeded { .. }
+            | Self::BinaryContent { .. }
+            | Self::FileTooLarge { .. }
+            | Self::SymlinkLoop(_)
+            | Self::SymlinkDenied(_) => ErrorCategory::User,
+            Self::Io(_)
+            | Self::CommandFailed(_)
+            | Self::Timeout(_)
+            | Self::CommandTimedOut { .. } => ErrorCategory::Environment,
+            Self::Other(_) => ErrorCategory::Other,
+        }
+    }
+
+    /// Whether retrying the same operation unchanged might succeed
+    ///
+    /// True for timeouts and transient I/O errors (interrupted, would block,
+    /// timed out). User errors are never retryable since the same request
+    /// would fail the same way.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use a3s_tools_core::ToolError;
+    ///
+    /// assert!(ToolError::Timeout(1000).is_retryable());
+    /// assert!(!ToolError::invalid_arg("offset", "must be positive").is_retryable());
+    /// ```
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::Timeout(_) | Self::CommandTimedOut { .. } => true,
+            Self::Io(err) => matches!(
+                err.kind(),
+                std::io::ErrorKind::Interrupted
+                    | std::io::ErrorKind::WouldBlock
+                    | std::io::ErrorKind::TimedOut
+            ),
+            _ => false,
+        }
+    }
+}
+
+/// Serde support for [`ToolError`]
+///
+/// Errors are represented as internally tagged objects, e.g.
+/// `{"type": "path_outside_workspace", "path": "../etc/passwd"}`.
+/// `std::io::Error` isn't serializable, so [`ToolError::Io`] is encoded as its
+/// kind and message, and deserialized into an equivalent `io::Error`.
+#[cfg(feature = "serde")]
+mod serde_impl {
+    use super::ToolError;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::io::ErrorKind;
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(tag = "type", rename_all = "snake_case")]
+    enum Repr {
+        PathNotFound {
+            path: String,
+        },
+        PathOutsideWorkspace {
+            path: String,
+        },
+        PathOutsideRoots {
+            path: String,
+            roots: Vec<String>,
+        },
+        PathsFailed {
+            failures: Vec<PathFailure>,
+        },
+        InvalidArgument {
+            name: String,
+            reason: String,
+        },
+        MissingArgument {
+            name: String,
+        },
+        Io {
+            kind: String,
+            message: String,
+        },
+        CommandFailed {
+            message: String,
+        },
+        LimitExceeded {
+            what: String,
+            limit: usize,
+            actual: usize,
+        },
+        BinaryContent {
+            size: usize,
+        },
+        FileTooLarge {
+            path: String,
+            size: u64,
+            max: u64,
+        },
+        SymlinkLoop {
+            path: String,
+        },
+        SymlinkDenied {
+            path: String,
+        },
+        Timeout {
+            timeout_ms: u64,
+        },
+        CommandTimedOut {
+            timeout_ms: u64,
+            stdout: String,
+            stderr: String,
+        },
+        Other {
+            message: String,
+        },
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct PathFailure {
+        path: String,
+        reason: String,
+    }
+
+    /// Stable names for the `io::ErrorKind`s we round-trip; anything else is `other`
+    const IO_KINDS: &[(ErrorKind, &str)] = &[
+        (ErrorKind::NotFound, "not_found"),
+        (ErrorKind::PermissionDenied, "permission_denied"),
+        (ErrorKind::ConnectionRefused, "connection_refused"),
+        (ErrorKind::ConnectionReset, "connection_reset"),
+        (ErrorKind::ConnectionAborted, "connection_aborted"),
+        (ErrorKind::NotConnected, "not_connected"),
+        (ErrorKind::AddrInUse, "addr_in_use"),
+        (ErrorKind::AddrNotAvailable, "addr_not_available"),
+        (ErrorKind::BrokenPipe, "broken_pipe"),
+        (ErrorKind::AlreadyExists, "already_exists"),
+        (ErrorKind::WouldBlock, "would_block"),
+        (ErrorKind::InvalidInput, "invalid_input"),
+        (ErrorKind::InvalidData, "invalid_data"),
+        (ErrorKind::TimedOut, "timed_out"),
+        (ErrorKind::WriteZero, "write_zero"),
+        (ErrorKind::Interrupted, "interrupted"),
+        (ErrorKind::Unsupported, "unsupported"),
+        (ErrorKind::UnexpectedEof, "unexpected_eof"),
+        (ErrorKind::OutOfMemory, "out_of_memory"),
+        (ErrorKind::NotADirectory, "not_a_directory"),
+        (ErrorKind::IsADirectory, "is_a_directory"),
+        (ErrorKind::DirectoryNotEmpty, "directory_not_empty"),
+        (ErrorKind::ReadOnlyFilesystem, "read_only_filesystem"),
+        (ErrorKind::StorageFull, "storage_full"),
+        (ErrorKind::FileTooLarge, "file_too_large"),
+        (ErrorKind::InvalidFilename, "invalid_filename"),
+        (ErrorKind::Other, "other"),
+    ];
+
+    fn kind_name(kind: ErrorKind) -> &'static str {
+        IO_KINDS
+            .iter()
+            .find(|(k, _)| *k == kind)
+            .map_or("other", |(_, name)| name)
+    }
+
+    fn kind_from_name(name: &str) -> ErrorKind {
+        IO_KINDS
+            .iter()
+            .find(|(_, n)| *n == name)
+            .map_or(ErrorKind::Other, |(kind, _)| *kind)
+    }
+
+    impl From<&ToolError> for Repr {
+        fn from(err: &ToolError) -> Self {
+            match err {
+                ToolError::PathNotFound(path) => Repr::PathNotFound { path: path.clone() },
+                ToolError::PathOutsideWorkspace(path) => {
+                    Repr::PathOutsideWorkspace { path: path.clone() }
+                }
+                ToolError::PathOutsideRoots { path, roots } => Repr::PathOutsideRoots {
+                    path: path.clone(),
+                    roots: roots.clone(),
+                },
+                ToolError::PathsFailed(failures) => Repr::PathsFailed {
+                    failures: failures
+                        .iter()
+                        .map(|(path, reason)| PathFailure {
+                            path: path.clone(),
+                            reason: reason.clone(),
+                        })
+                        .collect(),
+                },
+                ToolError::InvalidArgument { name, reason } => Repr::InvalidArgument {
+                    name: name.clone(),
+                    reason: reason.clone(),
+                },
+                ToolError::MissingArgument(name) => Repr::MissingArgument { name: name.clone() },
+                ToolError::Io(err) => Repr::Io {
+                    kind: kind_name(err.kind()).to_string(),
+                    message: err.to_string(),
+                },
+                ToolError::CommandFailed(message) => Repr::CommandFailed {
+                    message: message.clone(),
+                },
+                ToolError::LimitExceeded {
+                    what,
+                    limit,
+                    actual,
+                } => Repr::LimitExceeded {
+                    what: what.clone(),
+                    limit: *limit,
+                    actual: *actual,
+                },
+                ToolError::BinaryContent { size } => Repr::BinaryContent { size: *size },
+                ToolError::FileTooLarge { path, size, max } => Repr::FileTooLarge {
+                    path: path.clone(),
+                    size: *size,
+                    max: *max,
+                },
+                ToolError::SymlinkLoop(path) => Repr::SymlinkLoop { path: path.clone() },
+                ToolError::SymlinkDenied(path) => Repr::SymlinkDenied { path: path.clone() },
+                ToolError::Timeout(timeout_ms) => Repr::Timeout {
+                    timeout_ms: *timeout_ms,
+                },
+                ToolError::CommandTimedOut {
+                    timeout_ms,
+                    stdout,
+                    stderr,
+                } => Repr::CommandTimedOut {
+                    timeout_ms: *timeout_ms,
+                    stdout: stdout.clone(),
+                    stderr: stderr.clone(),
+                },
+                ToolError::Other(message) => Repr::Other {
+                    message: message.clone(),
+                },
+            }
+        }
+    }
+
+    impl From<Repr> for ToolError {
+        fn from(repr: Repr) -> Self {
+            match repr {
+                Repr::PathNotFound { path } => ToolError::PathNotFound(path),
+                Repr::PathOutsideWorkspace { path } => ToolError::PathOutsideWorkspace(path),
+                Repr::PathOutsideRoots { path, roots } => {
+                    ToolError::PathOutsideRoots { path, roots }
+                }
+                Repr::PathsFailed { failures } => ToolError::PathsFailed(
+                    failures
+                        .into_iter()
+                        .map(|failure| (failure.path, failure.reason))
+                        .collect(),
+                ),
+                Repr::InvalidArgument { name, reason } => {
+                    ToolError::InvalidArgument { name, reason }
+                }
+                Repr::MissingArgument { name } => ToolError::MissingArgument(name),
+                Repr::Io { kind, message } => {
+                    ToolError::Io(std::io::Error::new(kind_from_name(&kind), message))
+                }
+                Repr::CommandFailed { message } => ToolError::CommandFailed(message),
+                Repr::LimitExceeded {
+                    what,
+                    limit,
+                    actual,
+                } => ToolError::LimitExceeded {
+                    what,
+                    limit,
+                    actual,
+                },
+                Repr::BinaryContent { size } => ToolError::BinaryContent { size },
+                Repr::FileTooLarge { path, size, max } => {
+                    ToolError::FileTooLarge { path, size, max }
+                }
+                Repr::SymlinkLoop { path } => ToolError::SymlinkLoop(path),
+                Repr::SymlinkDenied { path } => ToolError::SymlinkDenied(path),
+                Repr::Timeout { timeout_ms } => ToolError::Timeout(timeout_ms),
+                Repr::CommandTimedOut {
+                    timeout_ms,
+                    stdout,
+                    stderr,
+                } => ToolError::CommandTimedOut {
+                    timeout_ms,
+                    stdout,
+                    stderr,
+                },
+                Repr::Other { message } => ToolError::Other(message),
+            }
+        }
+    }
+
+    impl Serialize for ToolError {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            Repr::from(self).serialize(serializer)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for ToolError {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            Repr::deserialize(deserializer).map(ToolError::from)
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use serde_json::json;
+
+        fn round_trip(err: ToolError) -> ToolError {
+            let json = serde_json::to_string(&err).unwrap();
+            let back: ToolError = serde_json::from_str(&json).unwrap();
+            assert_eq!(back.to_string(), err.to_string());
+            assert_eq!(
+                serde_json::to_value(&back).unwrap(),
+                serde_json::to_value(&err).unwrap()
+            );
+            back
+        }
+
+        #[test]
+        fn test_serialize_tagged_shape() {
+            let err = ToolError::PathOutsideWorkspace("../etc/passwd".to_string());
+            assert_eq!(
+                serde_json::to_value(&err).unwrap(),
+                json!({"type": "path_outside_workspace", "path": "../etc/passwd"})
+            );
+
+            let err = ToolError::invalid_arg("offset", "must be positive");
+            assert_eq!(
+                serde_json::to_value(&err).unwrap(),
+                json!({"type": "invalid_argument", "name": "offset", "reason": "must be positive"})
+            );
+
+            let err = ToolError::Timeout(5000);
+            assert_eq!(
+                serde_json::to_value(&err).unwrap(),
+                json!({"type": "timeout", "timeout_ms": 5000})
+            );
+        }
+
+        #[test]
+        fn test_round_trip_every_variant() {
+            assert!(matches!(
+                round_trip(ToolError::PathNotFound("a.txt".to_string())),
+                ToolError::PathNotFound(p) if p == "a.txt"
+            ));
+            assert!(matches!(
+                round_trip(ToolError::PathOutsideWorkspace("../x".to_string())),
+                ToolError::PathOutsideWorkspace(p) if p == "../x"
+            ));
+            assert!(matches!(
+                round_trip(ToolError::PathOutsideRoots {
+                    path: "/etc".to_string(),
+                    roots: vec!["/workspace".to_string(), "/opt".to_string()],
+                }),
+                ToolError::PathOutsideRoots { roots, .. } if roots.len() == 2
+            ));
+            assert!(matches!(
+                round_trip(ToolError::PathsFailed(vec![
+                    ("a.txt".to_string(), "Path not found: a.txt".to_string()),
+                    ("../b".to_string(), "Path '../b' is outside workspace".to_string()),
+                ])),
+                ToolError::PathsFailed(failures) if failures.len() == 2 && failures[1].0 == "../b"
+            ));
+            assert!(matches!(
+                round_trip(ToolError::invalid_arg("limit", "too big")),
+                ToolError::InvalidArgument { name, reason } if name == "limit" && reason == "too big"
+            ));
+            assert!(matches!(
+                round_trip(ToolError::missing_arg("content")),
+                ToolError::MissingArgument(n) if n == "content"
+            ));
+            assert!(matches!(
+                round_trip(ToolError::CommandFailed("exit 1".to_string())),
+                ToolError::CommandFailed(m) if m == "exit 1"
+            ));
+            assert!(matches!(
+                round_trip(ToolError::limit_exceeded("output size", 10, 20)),
+                ToolError::LimitExceeded {
+                    limit: 10,
+                    actual: 20,
+                    ..
+                }
+            ));
+            assert!(matches!(
+                round_trip(ToolError::BinaryContent { size: 48_213 }),
+                ToolError::BinaryContent { size: 48_213 }
+            ));
+            assert!(matches!(
+                round_trip(ToolError::FileTooLarge {
+                    path: "core".to_string(),
+                    size: 3 << 30,
+                    max: 10 << 20,
+                }),
+                ToolError::FileTooLarge { path, size, max: 10_485_760 }
+                    if path == "core" && size == 3 << 30
+            ));
+            assert!(matches!(
+                round_trip(ToolError::SymlinkLoop("a/b".to_string())),
+                ToolError::SymlinkLoop(p) if p == "a/b"
+            ));
+            assert!(matches!(
+                round_trip(ToolError::SymlinkDenied("link/file".to_string())),
+                ToolError::SymlinkDenied(p) if p == "link/file"
+            ));
+            assert!(matches!(
+                round_trip(ToolError::Timeout(120_000)),
+                ToolError::Timeout(120_000)
+            ));
+            assert!(matches!(
+                round_trip(ToolError::CommandTimedOut {
+                    timeout_ms: 500,
+                    stdout: "partial".to_string(),
+                    stderr: String::new(),
+                }),
+                ToolError::CommandTimedOut { timeout_ms: 500, stdout, .. } if stdout == "partial"
+            ));
+            assert!(matches!(
+                round_trip(ToolError::Other("oops".to_string())),
+                ToolError::Other(m) if m == "oops"
+            ));
+        }
+
+        #[test]
+        fn test_round_trip_io() {
+            let err = ToolError::Io(std::io::Error::new(
+                ErrorKind::PermissionDenied,
+                "access denied",
+            ));
+            assert_eq!(
+                serde_json::to_value(&err).unwrap(),
+                json!({"type": "io", "kind": "permission_denied", "message": "access denied"})
+            );
+
+            match round_trip(err) {
+                ToolError::Io(io) => {
+                    assert_eq!(io.kind(), ErrorKind::PermissionDenied);
+                    assert_eq!(io.to_string(), "access denied");
+                }
+                other => panic!("unexpected variant: {other:?}"),
+            }
+        }
+
+        #[test]
+        fn test_deserialize_unknown_io_kind() {
+            let err: ToolError = serde_json::from_value(
+                json!({"type": "io", "kind": "something_new", "message": "?"}),
+            )
+            .unwrap();
+            assert!(matches!(err, ToolError::Io(io) if io.kind() == ErrorKind::Other));
+        }
+    }
 }
 
 #[cfg(test)]
@@ -123,6 +715,24 @@ mod tests {
         let err = ToolError::PathOutsideWorkspace("../etc/passwd".to_string());
         assert_eq!(err.to_string(), "Path '../etc/passwd' is outside workspace");
 
+        let err = ToolError::PathOutsideRoots {
+            path: "/etc/passwd".to_string(),
+            roots: vec!["/workspace".to_string(), "/opt/models".to_string()],
+        };
+        assert_eq!(
+            err.to_string(),
+            "Path '/etc/passwd' is outside allowed roots: /workspace, /opt/models"
+        );
+
+        let err = ToolError::PathsFailed(vec![(
+            "missing.txt".to_string(),
+            "Path not found: missing.txt".to_string(),
+        )]);
+        assert_eq!(
+            err.to_string(),
+            "1 path failed:\n  missing.txt: Path not found: missing.txt"
+        );
+
         let err = ToolError::invalid_arg("file_path", "cannot be empty");
         assert_eq!(
             err.to_string(),
@@ -131,5 +741,104 @@ mod tests {
 
         let err = ToolError::missing_arg("content");
         assert_eq!(err.to_string(), "Missing required argument: content");
+
+        let err = ToolError::limit_exceeded("output size", 102_400, 250_000);
+        assert_eq!(
+            err.to_string(),
+            "output size limit exceeded: 250,000 > 102,400"
+        );
+
+        let err = ToolError::BinaryContent { size: 48_213 };
+        assert_eq!(
+            err.to_string(),
+            "Binary content (48,213 bytes) cannot be displayed"
+        );
+
+        let err = ToolError::CommandTimedOut {
+            timeout_ms: 5000,
+            stdout: "partial".to_string(),
+            stderr: String::new(),
+        };
+        assert_eq!(err.to_string(), "Command timed out after 5000ms");
+
+        let err = ToolError::FileTooLarge {
+            path: "core.dump".to_string(),
+            size: 3_113_851_290,
+            max: 10 * 1024 * 1024,
+        };
+        assert_eq!(
+            err.to_string(),
+            "File 'core.dump' is too large: 2.9 GiB exceeds limit of 10.0 MiB"
+        );
+
+        let err = ToolError::SymlinkLoop("a/file.txt".to_string());
+        assert_eq!(
+            err.to_string(),
+            "Symlink loop detected while resolving 'a/file.txt'"
+        );
+
+        let err = ToolError::SymlinkDenied("link/file.txt".to_string());
+        assert_eq!(
+            err.to_string(),
+            "Path 'link/file.txt' contains a symlink, which is not allowed"
+        );
+    }
+
+    #[test]
+    fn test_error_category() {
+        let user = [
+            ToolError::PathNotFound("a".to_string()),
+            ToolError::PathOutsideWorkspace("a".to_string()),
+            ToolError::PathOutsideRoots {
+                path: "a".to_string(),
+                roots: vec!["/workspace".to_string()],
+            },
+            ToolError::PathsFailed(vec![("a".to_string(), "b".to_string())]),
+            ToolError::invalid_arg("a", "b"),
+            ToolError::missing_arg("a"),
+            ToolError::limit_exceeded("output size", 1, 2),
+            ToolError::SymlinkDenied("a".to_string()),
+            ToolError::SymlinkLoop("a".to_string()),
+            ToolError::FileTooLarge {
+                path: "a".to_string(),
+                size: 2,
+                max: 1,
+            },
+            ToolError::BinaryContent { size: 10 },
+        ];
+        for err in &user {
+            assert_eq!(err.category(), ErrorCategory::User, "{err:?}");
+            assert!(!err.is_retryable(), "{err:?}");
+        }
+
+        let environment = [
+            ToolError::Io(std::io::Error::other("disk")),
+            ToolError::CommandFailed("exit 1".to_string()),
+            ToolError::Timeout(1000),
+            ToolError::CommandTimedOut {
+                timeout_ms: 1000,
+                stdout: String::new(),
+                stderr: String::new(),
+            },
+        ];
+        for err in &environment {
+            assert_eq!(err.category(), ErrorCategory::Environment, "{err:?}");
+        }
+
+        let other = ToolError::Other("?".to_string());
+        assert_eq!(other.category(), ErrorCategory::Other);
+        assert!(!other.is_retryable());
+    }
+
+    #[test]
+    fn test_error_is_retryable() {
+        assert!(ToolError::Timeout(1000).is_retryable());
+        assert!(!ToolError::CommandFailed("exit 1".to_string()).is_retryable());
+
+        let transient = std::io::Error::from(std::io::ErrorKind::Interrupted);
+        assert!(ToolError::Io(transient).is_retryable());
+
+        let permanent = std::io::Error::from(std::io::ErrorKind::PermissionDenied);
+        assert!(!ToolError::Io(permanent).is_retryable());
     }
 }
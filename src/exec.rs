@@ -0,0 +1,364 @@
+//! Running commands with bounded output and a deadline
+//!
+//! [`run_command`] is the shared way for tools to shell out. Output is read
+//! incrementally as the child produces it, and only the first
+//! [`MAX_OUTPUT_SIZE`] bytes (stdout and stderr combined) are kept, so a
+//! process printing gigabytes can't exhaust memory.
+//!
+//! On Unix the child runs in its own process group, so a timeout kills
+//! everything it started (e.g. the `sleep` in `sh -c "sleep 60"`), not just
+//! the shell.
+
+use crate::constants::MAX_OUTPUT_SIZE;
+use crate::sandbox::Sandbox;
+use crate::timeout::validate_timeout;
+use crate::ToolError;
+use std::io::Read;
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How often to check whether the child has exited
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// How long to wait for pipes to drain once the child has exited or been killed
+///
+/// Background processes that inherited the pipes can keep them open after the
+/// child is gone; their output is abandoned rather than waited for.
+const DRAIN_GRACE: Duration = Duration::from_millis(100);
+
+/// Output of a command run with [`run_command`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandOutput {
+    /// Exit code, or `None` if the process was terminated by a signal
+    pub exit_code: Option<i32>,
+    /// Captured standard output (lossily decoded as UTF-8)
+    pub stdout: String,
+    /// Captured standard error (lossily decoded as UTF-8)
+    pub stderr: String,
+    /// Whether output was dropped after reaching the size cap
+    pub truncated: bool,
+}
+
+impl CommandOutput {
+    /// Whether the command exited with code 0
+    pub fn success(&self) -> bool {
+        self.exit_code == Some(0)
+    }
+}
+
+/// Run a command in a sandboxed working directory
+///
+/// The child gets a null stdin. Its stdout and stderr are captured up to
+/// [`MAX_OUTPUT_SIZE`] bytes combined; anything beyond that is read and
+/// discarded so the child never blocks on a full pipe.
+/// Once the child exits, output from background processes still holding its
+/// pipes is collected only briefly; they are left running.
+///
+/// # Arguments
+///
+/// * `workspace` - The workspace root directory (sandbox boundary)
+/// * `program` - The program to run (looked up on `PATH` if not a path)
+/// * `args` - Arguments passed to the program
+/// * `cwd` - Working directory, relative to the workspace or absolute within it
+/// * `timeout_ms` - Timeout in milliseconds, validated with [`validate_timeout`]
+///
+/// # Returns
+///
+/// * `Ok(CommandOutput)` - The command ran to completion (with any exit code)
+/// * `Err(ToolError::PathNotFound)` - If `cwd` doesn't exist
+/// * `Err(ToolError::PathOutsideWorkspace)` - If `cwd` is outside the workspace
+/// * `Err(ToolError::InvalidArgument)` - If `cwd` isn't a directory or the timeout is invalid
+/// * `Err(ToolError::CommandFailed)` - If the program couldn't be started
+/// * `Err(ToolError::CommandTimedOut)` - If the timeout expired; the child is
+///   killed and the output captured so far is attached
+///
+/// # Examples
+///
+/// ```rust
+/// # #[cfg(unix)]
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use a3s_tools_core::run_command;
+///
+/// let dir = tempfile::tempdir()?;
+/// let output = run_command(dir.path(), "sh", &["-c", "echo hi; exit 3"], ".", Some(5_000))?;
+/// assert_eq!(output.stdout, "hi\n");
+/// assert_eq!(output.exit_code, Some(3));
+/// assert!(!output.success());
+/// # Ok(())
+/// # }
+/// # #[cfg(not(unix))]
+/// # fn main() {}
+/// ```
+pub fn run_command(
+    workspace: &Path,
+    program: &str,
+    args: &[&str],
+    cwd: &str,
+    timeout_ms: Option<u64>,
+) -> Result<CommandOutput, ToolError> {
+    let timeout = validate_timeout(timeout_ms)?;
+    let dir = Sandbox::lenient(workspace).resolve(cwd)?;
+    if !dir.is_dir() {
+        return Err(ToolError::invalid_arg(
+            "cwd",
+            format!("'{}' is not a directory", cwd),
+        ));
+    }
+
+    let mut command = Command::new(program);
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(&mut command, 0);
+
+    let mut child = command
+        .args(args)
+        .current_dir(&dir)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|err| {
+            ToolError::CommandFailed(format!("failed to start '{}': {}", program, err))
+        })?;
+
+    let deadline = Instant::now() + timeout;
+    let budget = Arc::new(AtomicUsize::new(MAX_OUTPUT_SIZE));
+    let truncated = Arc::new(AtomicBool::new(false));
+    let (done_tx, done_rx) = mpsc::channel();
+
+    let stdout = Capture::spawn(child.stdout.take(), &budget, &truncated, done_tx.clone());
+    let stderr = Capture::spawn(child.stderr.take(), &budget, &truncated, done_tx);
+
+    let status = wait_until(&mut child, deadline)?;
+    let finished = |grace: Duration| {
+        // One message per reader once its pipe reaches EOF
+        (0..2).all(|_| done_rx.recv_timeout(grace).is_ok())
+    };
+
+    let status = match status {
+        Some(status) => {
+            finished(DRAIN_GRACE);
+            status
+        }
+        None => {
+            kill_process_group(&mut child);
+            let _ = child.wait();
+            finished(DRAIN_GRACE);
+            return Err(ToolError::CommandTimedOut {
+                timeout_ms: u64::try_from(timeout.as_millis()).unwrap_or(u64::MAX),
+                stdout: stdout.text(),
+                stderr: stderr.text(),
+            });
+        }
+    };
+
+    Ok(CommandOutput {
+        exit_code: status.code(),
+        stdout: stdout.text(),
+        stderr: stderr.text(),
+        truncated: truncated.load(Ordering::Relaxed),
+    })
+}
+
+/// Poll `child` until it exits or `deadline` passes (returning `None`)
+fn wait_until(
+    child: &mut Child,
+    deadline: Instant,
+) -> Result<Option<std::process::ExitStatus>, ToolError> {
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(Some(status));
+        }
+        let now = Instant::now();
+        if now >= deadline {
+            return Ok(None);
+        }
+        thread::sleep(POLL_INTERVAL.min(deadline - now));
+    }
+}
+
+/// Kill `child` and, on Unix, every process in its process group
+///
+/// Must be called before `child` is reaped, so its pid (the group id) can't
+/// have been reused.
+fn kill_process_group(child: &mut Child) {
+    #[cfg(unix)]
+    if let Ok(pgid) = libc::pid_t::try_from(child.id()) {
+        // SAFETY: killpg has no memory-safety preconditions; the group was
+        // created by `process_group(0)` and its leader hasn't been reaped
+        unsafe {
+            libc::killpg(pgid, libc::SIGKILL);
+        }
+    }
+    let _ = child.kill();
+}
+
+/// A stream being drained into a shared, size-capped buffer on a reader thread
+struct Capture {
+    buffer: Arc<Mutex<Vec<u8>>>,
+}
+
+impl Capture {
+    fn spawn(
+        stream: Option<impl Read + Send + 'static>,
+        budget: &Arc<AtomicUsize>,
+        truncated: &Arc<AtomicBool>,
+        done: mpsc::Sender<()>,
+    ) -> Self {
+        let buffer = Arc::new(Mutex::new(Vec::new()));
+        let (shared, budget, truncated) = (buffer.clone(), budget.clone(), truncated.clone());
+
+        thread::spawn(move || {
+            if let Some(mut stream) = stream {
+                let mut chunk = [0u8; 8192];
+                loop {
+                    let n = match stream.read(&mut chunk) {
+                        Ok(0) => break,
+                        Ok(n) => n,
+                        Err(err) if err.kind() == std::io::ErrorKind::Interrupted => continue,
+                        Err(_) => break,
+                    };
+                    // Claim up to `n` bytes of the combined budget
+                    let claimed = budget
+                        .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |left| {
+                            Some(left - left.min(n))
+                        })
+                        .map_or(0, |left| left.min(n));
+                    if claimed < n {
+                        truncated.store(true, Ordering::Relaxed);
+                    }
+                    if claimed > 0 {
+                        shared.lock().unwrap().extend_from_slice(&chunk[..claimed]);
+                    }
+                }
+            }
+            let _ = done.send(());
+        });
+
+        Self { buffer }
+    }
+
+    /// Everything captured so far, lossily decoded
+    fn text(&self) -> String {
+        String::from_utf8_lossy(&self.buffer.lock().unwrap()).into_owned()
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    fn sh(workspace: &Path, script: &str, timeout_ms: u64) -> Result<CommandOutput, ToolError> {
+        run_command(workspace, "sh", &["-c", script], ".", Some(timeout_ms))
+    }
+
+    #[test]
+    fn test_run_command_success() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let output = sh(temp_dir.path(), "echo out; echo err >&2", 5_000).unwrap();
+
+        assert!(output.success());
+        assert_eq!(output.stdout, "out\n");
+        assert_eq!(output.stderr, "err\n");
+        assert!(!output.truncated);
+    }
+
+    #[test]
+    fn test_run_command_nonzero_exit() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let output = sh(temp_dir.path(), "echo failing >&2; exit 42", 5_000).unwrap();
+
+        assert_eq!(output.exit_code, Some(42));
+        assert!(!output.success());
+        assert_eq!(output.stderr, "failing\n");
+    }
+
+    #[test]
+    fn test_run_command_timeout_kills_child() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let start = Instant::now();
+        let result = sh(temp_dir.path(), "echo started; sleep 30", 300);
+
+        assert!(start.elapsed() < Duration::from_secs(10));
+        match result {
+            Err(ToolError::CommandTimedOut {
+                timeout_ms, stdout, ..
+            }) => {
+                assert_eq!(timeout_ms, 300);
+                assert_eq!(stdout, "started\n");
+            }
+            other => panic!("unexpected result: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_run_command_timeout_kills_process_group() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let workspace = temp_dir.path();
+        let result = sh(workspace, "(sleep 1; touch marker) & sleep 30", 300);
+        assert!(matches!(result, Err(ToolError::CommandTimedOut { .. })));
+
+        // The background subshell was killed along with `sh`
+        thread::sleep(Duration::from_millis(1_500));
+        assert!(!workspace.join("marker").exists());
+    }
+
+    #[test]
+    fn test_run_command_background_process_keeps_pipes() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let start = Instant::now();
+        let output = sh(temp_dir.path(), "sleep 5 & echo hi; exit 0", 60_000).unwrap();
+
+        assert!(start.elapsed() < Duration::from_secs(3));
+        assert!(output.success());
+        assert_eq!(output.stdout, "hi\n");
+    }
+
+    #[test]
+    fn test_run_command_flood_is_capped() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        // ~10 MB on each stream
+        let script = "head -c 10000000 /dev/zero | tr '\\0' x & \
+                      head -c 10000000 /dev/zero | tr '\\0' y >&2; wait";
+        let output = sh(temp_dir.path(), script, 60_000).unwrap();
+
+        assert!(output.success());
+        assert!(output.truncated);
+        assert_eq!(output.stdout.len() + output.stderr.len(), MAX_OUTPUT_SIZE);
+        assert!(output.stdout.bytes().all(|b| b == b'x'));
+        assert!(output.stderr.bytes().all(|b| b == b'y'));
+    }
+
+    #[test]
+    fn test_run_command_cwd_is_sandboxed() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let workspace = temp_dir.path();
+        std::fs::create_dir(workspace.join("sub")).unwrap();
+        std::fs::write(workspace.join("file.txt"), "").unwrap();
+
+        let output = run_command(workspace, "pwd", &[], "sub", None).unwrap();
+        let expected = workspace.join("sub").canonicalize().unwrap();
+        assert_eq!(output.stdout.trim_end(), expected.to_str().unwrap());
+
+        let result = run_command(workspace, "pwd", &[], "..", None);
+        assert!(matches!(result, Err(ToolError::PathOutsideWorkspace(_))));
+
+        let result = run_command(workspace, "pwd", &[], "file.txt", None);
+        assert!(matches!(result, Err(ToolError::InvalidArgument { .. })));
+    }
+
+    #[test]
+    fn test_run_command_invalid_inputs() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let workspace = temp_dir.path();
+
+        let result = run_command(workspace, "definitely-not-a-program-a3s", &[], ".", None);
+        assert!(matches!(result, Err(ToolError::CommandFailed(_))));
+
+        let result = run_command(workspace, "true", &[], ".", Some(0));
+        assert!(matches!(result, Err(ToolError::InvalidArgument { .. })));
+    }
+}
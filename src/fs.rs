@@ -0,0 +1,680 @@
+//! Sandboxed file helpers
+//!
+//! This module provides file operations built on the sandbox path resolution,
+//! so tools don't each reimplement resolve + create directories + write.
+
+use crate::constants::{MAX_FILE_READ_BYTES, MAX_LINE_LENGTH, MAX_OUTPUT_SIZE, MAX_READ_LINES};
+use crate::output::{decode_text, format_count, is_probably_binary, render_numbered, utf16_bom};
+use crate::sandbox::{resolve_path, Sandbox};
+use crate::ToolError;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Cursor, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Counter making temp file names unique within this process
+static TEMP_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Atomically write a file inside the workspace
+///
+/// Resolves `path` with [`resolve_path_for_write`](crate::resolve_path_for_write),
+/// creates any missing parent directories, writes `contents` to a temporary
+/// file in the same directory, fsyncs it, and renames it into place. Readers
+/// see either the old file or the new one, never a partial write.
+///
+/// When overwriting, the existing file's permissions are preserved.
+///
+/// # Arguments
+///
+/// * `workspace` - The workspace root directory (sandbox boundary)
+/// * `path` - The path to write (can be relative or absolute)
+/// * `contents` - The bytes to write
+///
+/// # Returns
+///
+/// * `Ok(PathBuf)` - The final resolved path that was written
+/// * `Err(ToolError::PathOutsideWorkspace)` - If the path would be outside workspace
+/// * `Err(ToolError::Io)` - If creating directories, writing, or renaming fails;
+///   the temporary file is removed in that case
+///
+/// # Security
+///
+/// The path is re-resolved after parent directories are created, so a
+/// directory swapped for a symlink in the meantime is still caught.
+///
+/// # Examples
+///
+/// ```rust
+/// use a3s_tools_core::write_file_atomic;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let dir = tempfile::tempdir()?;
+///
+/// let path = write_file_atomic(dir.path(), "out/report.txt", b"done\n")?;
+/// assert_eq!(std::fs::read(&path)?, b"done\n");
+///
+/// assert!(write_file_atomic(dir.path(), "../escape.txt", b"x").is_err());
+/// # Ok(())
+/// # }
+/// ```
+pub fn write_file_atomic(
+    workspace: &Path,
+    path: &str,
+    contents: &[u8],
+) -> Result<PathBuf, ToolError> {
+    let sandbox = Sandbox::lenient(workspace);
+    let resolved = sandbox.resolve_for_write(path)?;
+
+    let parent = resolved
+        .parent()
+        .ok_or_else(|| ToolError::invalid_arg("path", "cannot write to the filesystem root"))?;
+    fs::create_dir_all(parent)?;
+
+    // Re-resolve now that the parents exist, so they are canonicalized and checked
+    let resolved = sandbox.resolve_for_write(path)?;
+    let parent = resolved
+        .parent()
+        .ok_or_else(|| ToolError::invalid_arg("path", "cannot write to the filesystem root"))?;
+
+    let file_name = resolved
+        .file_name()
+        .ok_or_else(|| ToolError::invalid_arg("path", "must name a file"))?;
+
+    let existing_permissions = fs::metadata(&resolved)
+        .ok()
+        .filter(|metadata| metadata.is_file())
+        .map(|metadata| metadata.permissions());
+
+    let mut temp_name = std::ffi::OsString::from(".");
+    temp_name.push(file_name);
+    temp_name.push(format!(
+        ".{}.{}.tmp",
+        std::process::id(),
+        TEMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    let temp = TempFile::new(parent.join(temp_name));
+
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&temp.path)?;
+    file.write_all(contents)?;
+    if let Some(permissions) = existing_permissions {
+        file.set_permissions(permissions)?;
+    }
+    file.sync_all()?;
+    drop(file);
+
+    fs::rename(&temp.path, &resolved)?;
+    temp.persist();
+
+    // Persist the rename itself; not supported on every platform, so best effort
+    if let Ok(dir) = File::open(parent) {
+        let _ = dir.sync_all();
+    }
+
+    Ok(resolved)
+}
+
+/// Read a file inside the workspace and format it with line numbers
+///
+/// Combines [`resolve_path`], reading and [`format_line_numbered`](crate::format_line_numbered)
+/// into one call. Output stops at the last whole line that fits in
+/// [`MAX_OUTPUT_SIZE`], so the "read more" note always points at the first
+/// line not shown. Files larger than
+/// [`MAX_FILE_READ_BYTES`] are refused; see [`read_file_formatted_with_budget`]
+/// to use a different budget.
+///
+/// The file is streamed line by line: lines before `offset` are skipped
+/// without being stored, so reading near the end of a large log doesn't
+/// allocate the whole file.
+///
+/// # Arguments
+///
+/// * `workspace` - The workspace root directory (sandbox boundary)
+/// * `path` - The file to read (can be relative or absolute)
+/// * `offset` - Number of lines to skip (0-indexed)
+/// * `limit` - Maximum number of lines to show; `None` (or `Some(0)`) means [`MAX_READ_LINES`]
+///
+/// # Returns
+///
+/// * `Ok(String)` - The line-numbered content, followed by a note when lines
+///   after the shown range were omitted (by `limit` or the output size cap)
+///   or `offset` is past the end
+/// * `Err(ToolError::PathNotFound)` / `Err(ToolError::PathOutsideWorkspace)` - From resolution
+/// * `Err(ToolError::FileTooLarge)` - If the file is larger than the byte budget
+/// * `Err(ToolError::BinaryContent)` - If the file looks binary
+///
+/// Invalid UTF-8 is replaced with `U+FFFD` rather than failing. Files with a
+/// UTF-16 byte order mark are decoded as UTF-16, as in
+/// [`format_file_content`](crate::format_file_content).
+///
+/// # Examples
+///
+/// ```rust
+/// use a3s_tools_core::read_file_formatted;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let dir = tempfile::tempdir()?;
+/// std::fs::write(dir.path().join("notes.txt"), "a\nb\nc\nd\n")?;
+///
+/// let text = read_file_formatted(dir.path(), "notes.txt", 1, Some(2))?;
+/// assert_eq!(text, "2\tb\n3\tc\n\n[Showing lines 2-3 of 4. Use offset=3 to read more.]");
+/// # Ok(())
+/// # }
+/// ```
+pub fn read_file_formatted(
+    workspace: &Path,
+    path: &str,
+    offset: usize,
+    limit: Option<usize>,
+) -> Result<String, ToolError> {
+    read_file_formatted_with_budget(workspace, path, offset, limit, MAX_FILE_READ_BYTES)
+}
+
+/// Read and format a file with a custom byte budget
+///
+/// Same as [`read_file_formatted`], but refuses files larger than `max_bytes`
+/// instead of [`MAX_FILE_READ_BYTES`].
+pub fn read_file_formatted_with_budget(
+    workspace: &Path,
+    path: &str,
+    offset: usize,
+    limit: Option<usize>,
+    max_bytes: u64,
+) -> Result<String, ToolError> {
+    let resolved = resolve_path(workspace, path)?;
+    let size = check_file_size(&resolved, max_bytes)?;
+
+    let limit = match limit {
+        None | Some(0) => MAX_READ_LINES,
+        Some(limit) => limit,
+    };
+
+    let mut file = BufReader::new(File::open(&resolved)?);
+    let head = file.fill_buf()?;
+    if is_probably_binary(head) {
+        return Err(ToolError::BinaryContent {
+            size: usize::try_from(size).unwrap_or(usize::MAX),
+        });
+    }
+
+    let mut reader: Box<dyn BufRead> = if utf16_bom(head).is_some() {
+        // UTF-16 can't be split on b'\n'; the size check bounds decoding it whole
+        let mut bytes = Vec::new();
+        std::io::Read::read_to_end(&mut file, &mut bytes)?;
+        Box::new(Cursor::new(decode_text(&bytes).into_owned().into_bytes()))
+    } else {
+        // Same as format_line_numbered: don't glue a UTF-8 BOM to line 1
+        if head.starts_with(b"\xEF\xBB\xBF") {
+            file.consume(3);
+        }
+        Box::new(file)
+    };
+
+    let mut skipped = 0;
+    while skipped < offset && skip_line(&mut reader)? {
+        skipped += 1;
+    }
+
+    let mut lines = Vec::new();
+    let mut buf = Vec::new();
+    while lines.len() < limit {
+        buf.clear();
+        if reader.read_until(b'\n', &mut buf)? == 0 {
+            break;
+        }
+        let line = buf.strip_suffix(b"\n").unwrap_or(&buf);
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        lines.push(String::from_utf8_lossy(line).into_owned());
+    }
+
+    let mut remaining = 0;
+    while skip_line(&mut reader)? {
+        remaining += 1;
+    }
+
+    let line_refs: Vec<&str> = lines.iter().map(String::as_str).collect();
+    let mut output = render_numbered(&line_refs, offset, MAX_LINE_LENGTH);
+
+    // Keep whole lines within the output budget (always at least one, which
+    // MAX_LINE_LENGTH keeps well under it)
+    let mut shown = 0;
+    let mut used = 0;
+    for rendered in output.split('\n').take(lines.len()) {
+        used += rendered.len() + usize::from(shown > 0);
+        if shown > 0 && used > MAX_OUTPUT_SIZE {
+            break;
+        }
+        shown += 1;
+    }
+    if shown < lines.len() {
+        output = render_numbered(&line_refs[..shown], offset, MAX_LINE_LENGTH);
+    }
+
+    let total = skipped + lines.len() + remaining;
+    if lines.is_empty() && offset > 0 && offset >= total {
+        output = format!(
+            "[Offset {} is past the end of the file ({} lines)]",
+            format_count(offset),
+            format_count(total)
+        );
+    } else if offset + shown < total {
+        output.push_str(&format!(
+            "\n\n[Showing lines {}-{} of {}. Use offset={} to read more.]",
+            format_count(offset + 1),
+            format_count(offset + shown),
+            format_count(total),
+            offset + shown
+        ));
+    }
+
+    Ok(output)
+}
+
+/// Check a file's size before reading it
+///
+/// # Arguments
+///
+/// * `path` - An already-resolved path, e.g. from [`resolve_path`] or [`Sandbox::resolve`]
+/// * `max_bytes` - The largest size accepted, inclusive
+///
+/// # Returns
+///
+/// * `Ok(u64)` - The file's size in bytes
+/// * `Err(ToolError::PathNotFound)` - If the file doesn't exist
+/// * `Err(ToolError::InvalidArgument)` - If the path is a directory
+/// * `Err(ToolError::FileTooLarge)` - If the file is larger than `max_bytes`
+///
+/// # Examples
+///
+/// ```rust
+/// use a3s_tools_core::{check_file_size, resolve_path, ToolError, MAX_FILE_READ_BYTES};
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let dir = tempfile::tempdir()?;
+/// std::fs::write(dir.path().join("notes.txt"), "hello")?;
+///
+/// let path = resolve_path(dir.path(), "notes.txt")?;
+/// assert_eq!(check_file_size(&path, MAX_FILE_READ_BYTES)?, 5);
+/// assert!(matches!(check_file_size(&path, 4), Err(ToolError::FileTooLarge { .. })));
+/// # Ok(())
+/// # }
+/// ```
+pub fn check_file_size(path: &Path, max_bytes: u64) -> Result<u64, ToolError> {
+    let metadata = fs::metadata(path).map_err(|err| match err.kind() {
+        std::io::ErrorKind::NotFound => ToolError::PathNotFound(path.display().to_string()),
+        _ => ToolError::Io(err),
+    })?;
+
+    if metadata.is_dir() {
+        return Err(ToolError::invalid_arg(
+            "path",
+            format!("'{}' is a directory", path.display()),
+        ));
+    }
+
+    let size = metadata.len();
+    if size > max_bytes {
+        return Err(ToolError::FileTooLarge {
+            path: path.display().to_string(),
+            size,
+            max: max_bytes,
+        });
+    }
+
+    Ok(size)
+}
+
+/// Skip one line without storing it, returning `false` at end of input
+fn skip_line(reader: &mut impl BufRead) -> std::io::Result<bool> {
+    let mut consumed_any = false;
+    loop {
+        let buf = reader.fill_buf()?;
+        if buf.is_empty() {
+            return Ok(consumed_any);
+        }
+        match buf.iter().position(|&b| b == b'\n') {
+            Some(pos) => {
+                reader.consume(pos + 1);
+                return Ok(true);
+            }
+            None => {
+                let len = buf.len();
+                reader.consume(len);
+                consumed_any = true;
+            }
+        }
+    }
+}
+
+/// A temporary file removed on drop unless persisted
+struct TempFile {
+    path: PathBuf,
+    persisted: bool,
+}
+
+impl TempFile {
+    fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            persisted: false,
+        }
+    }
+
+    /// Keep the file (it has been renamed into place)
+    fn persist(mut self) {
+        self.persisted = true;
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        if !self.persisted {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entries(dir: &Path) -> Vec<String> {
+        let mut names: Vec<String> = fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn test_write_file_atomic_new_file() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let workspace = temp_dir.path();
+
+        let path = write_file_atomic(workspace, "a/b/new.txt", b"hello").unwrap();
+
+        assert_eq!(path, workspace.canonicalize().unwrap().join("a/b/new.txt"));
+        assert_eq!(fs::read(&path).unwrap(), b"hello");
+        assert_eq!(entries(&workspace.join("a/b")), vec!["new.txt"]);
+    }
+
+    #[test]
+    fn test_write_file_atomic_overwrite() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let workspace = temp_dir.path();
+        fs::write(workspace.join("file.txt"), "old contents").unwrap();
+
+        let path = write_file_atomic(workspace, "file.txt", b"new").unwrap();
+
+        assert_eq!(fs::read(&path).unwrap(), b"new");
+        assert_eq!(entries(workspace), vec!["file.txt"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_write_file_atomic_preserves_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let workspace = temp_dir.path();
+        let script = workspace.join("run.sh");
+        fs::write(&script, "#!/bin/sh\n").unwrap();
+        fs::set_permissions(&script, fs::Permissions::from_mode(0o750)).unwrap();
+
+        write_file_atomic(workspace, "run.sh", b"#!/bin/sh\necho hi\n").unwrap();
+
+        let mode = fs::metadata(&script).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o750);
+    }
+
+    #[test]
+    fn test_write_file_atomic_rejects_escape() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let workspace = temp_dir.path().join("workspace");
+        fs::create_dir(&workspace).unwrap();
+
+        let result = write_file_atomic(&workspace, "../escape.txt", b"x");
+        assert!(matches!(result, Err(ToolError::PathOutsideWorkspace(_))));
+
+        let result = write_file_atomic(&workspace, "/tmp/escape/deep.txt", b"x");
+        assert!(matches!(result, Err(ToolError::PathOutsideWorkspace(_))));
+        assert!(!temp_dir.path().join("escape.txt").exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_write_file_atomic_rejects_symlinked_parent() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let workspace = temp_dir.path();
+        let outside = tempfile::tempdir().unwrap();
+        std::os::unix::fs::symlink(outside.path(), workspace.join("link")).unwrap();
+
+        let result = write_file_atomic(workspace, "link/new/file.txt", b"x");
+        assert!(matches!(result, Err(ToolError::PathOutsideWorkspace(_))));
+        assert!(entries(outside.path()).is_empty());
+    }
+
+    #[test]
+    fn test_write_file_atomic_cleans_up_temp_on_error() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let workspace = temp_dir.path();
+
+        // Renaming a file over a non-empty directory fails after the temp file is written
+        fs::create_dir(workspace.join("target")).unwrap();
+        fs::write(workspace.join("target/inner.txt"), "x").unwrap();
+
+        let result = write_file_atomic(workspace, "target", b"contents");
+        assert!(matches!(result, Err(ToolError::Io(_))));
+        assert_eq!(entries(workspace), vec!["target"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_write_file_atomic_read_only_directory() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let workspace = temp_dir.path();
+        let locked = workspace.join("locked");
+        fs::create_dir(&locked).unwrap();
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o555)).unwrap();
+
+        // Privileged users (e.g. root in CI containers) bypass directory permissions
+        let bypassed = File::create(locked.join("probe")).is_ok();
+        let result = write_file_atomic(workspace, "locked/file.txt", b"x");
+
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o755)).unwrap();
+        if bypassed {
+            return;
+        }
+        assert!(matches!(result, Err(ToolError::Io(_))));
+        assert!(entries(&locked).is_empty());
+    }
+
+    #[test]
+    fn test_read_file_formatted_large_file_with_offset() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let workspace = temp_dir.path();
+        let content: String = (1..=5_000).map(|i| format!("line {i}\n")).collect();
+        fs::write(workspace.join("big.log"), &content).unwrap();
+
+        let text = read_file_formatted(workspace, "big.log", 4_000, Some(3)).unwrap();
+        assert_eq!(
+            text,
+            "4001\tline 4001\n4002\tline 4002\n4003\tline 4003\n\n\
+             [Showing lines 4,001-4,003 of 5,000. Use offset=4003 to read more.]"
+        );
+
+        // Default limit is MAX_READ_LINES
+        let text = read_file_formatted(workspace, "big.log", 0, None).unwrap();
+        assert!(text.contains("2000\tline 2000\n\n[Showing lines 1-2,000 of 5,000."));
+        assert!(!text.contains("line 2001"));
+
+        // Reading to the end has no trailing note
+        let text = read_file_formatted(workspace, "big.log", 4_998, None).unwrap();
+        assert_eq!(text, "4999\tline 4999\n5000\tline 5000");
+    }
+
+    #[test]
+    fn test_read_file_formatted_long_lines_hit_output_cap() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let workspace = temp_dir.path();
+        let content: String = (1..=3_000).map(|i| format!("{i:0>99}\n")).collect();
+        fs::write(workspace.join("wide.txt"), &content).unwrap();
+
+        let text = read_file_formatted(workspace, "wide.txt", 0, None).unwrap();
+        assert!(!text.contains("[Output truncated"));
+
+        let (body, note) = text.rsplit_once("\n\n").unwrap();
+        assert!(body.len() <= MAX_OUTPUT_SIZE);
+        let last_line = body.lines().last().unwrap();
+        let (number, rest) = last_line.split_once('\t').unwrap();
+        let shown: usize = number.trim().parse().unwrap();
+        assert!(shown < 2_000);
+        assert_eq!(rest, format!("{shown:0>99}"));
+        assert_eq!(
+            note,
+            format!(
+                "[Showing lines 1-{} of 3,000. Use offset={shown} to read more.]",
+                format_count(shown)
+            )
+        );
+
+        // Following the hint continues with the next line
+        let text = read_file_formatted(workspace, "wide.txt", shown, Some(1)).unwrap();
+        assert!(text.starts_with(&format!("{}\t", shown + 1)));
+    }
+
+    #[test]
+    fn test_read_file_formatted_offset_past_end() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let workspace = temp_dir.path();
+        fs::write(workspace.join("short.txt"), "a\nb\n").unwrap();
+
+        let text = read_file_formatted(workspace, "short.txt", 10, None).unwrap();
+        assert_eq!(text, "[Offset 10 is past the end of the file (2 lines)]");
+    }
+
+    #[test]
+    fn test_read_file_formatted_exactly_at_budget() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let workspace = temp_dir.path();
+        fs::write(workspace.join("file.txt"), "0123456789").unwrap();
+
+        let text = read_file_formatted_with_budget(workspace, "file.txt", 0, None, 10).unwrap();
+        assert_eq!(text, "1\t0123456789");
+
+        let err = read_file_formatted_with_budget(workspace, "file.txt", 0, None, 9).unwrap_err();
+        assert!(matches!(
+            err,
+            ToolError::FileTooLarge {
+                size: 10,
+                max: 9,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_check_file_size_exactly_at_limit() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let file = temp_dir.path().join("file.bin");
+        fs::write(&file, vec![0u8; 1024]).unwrap();
+
+        assert_eq!(check_file_size(&file, 1024).unwrap(), 1024);
+
+        let err = check_file_size(&file, 1023).unwrap_err();
+        assert!(matches!(
+            err,
+            ToolError::FileTooLarge {
+                size: 1024,
+                max: 1023,
+                ..
+            }
+        ));
+        assert!(err
+            .to_string()
+            .ends_with("is too large: 1.0 KiB exceeds limit of 1023 bytes"));
+    }
+
+    #[test]
+    fn test_check_file_size_missing_or_directory() {
+        let temp_dir = tempfile::tempdir().unwrap();
+
+        let result = check_file_size(&temp_dir.path().join("missing.txt"), MAX_FILE_READ_BYTES);
+        assert!(matches!(result, Err(ToolError::PathNotFound(_))));
+
+        let result = check_file_size(temp_dir.path(), MAX_FILE_READ_BYTES);
+        assert!(matches!(result, Err(ToolError::InvalidArgument { .. })));
+    }
+
+    #[test]
+    fn test_read_file_formatted_crlf_and_bom() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let workspace = temp_dir.path();
+        fs::write(workspace.join("win.txt"), "\u{FEFF}first\r\nsecond\r\n").unwrap();
+
+        let text = read_file_formatted(workspace, "win.txt", 0, None).unwrap();
+        assert_eq!(text, "1\tfirst\n2\tsecond");
+    }
+
+    #[test]
+    fn test_read_file_formatted_utf16() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let workspace = temp_dir.path();
+        let text = "hello\r\nwörld\r\nthird\r\n";
+
+        let mut le = vec![0xFF, 0xFE];
+        le.extend(text.encode_utf16().flat_map(u16::to_le_bytes));
+        fs::write(workspace.join("le.txt"), le).unwrap();
+        let mut be = vec![0xFE, 0xFF];
+        be.extend(text.encode_utf16().flat_map(u16::to_be_bytes));
+        fs::write(workspace.join("be.txt"), be).unwrap();
+
+        for path in ["le.txt", "be.txt"] {
+            let output = read_file_formatted(workspace, path, 1, Some(1)).unwrap();
+            assert_eq!(
+                output,
+                "2\twörld\n\n[Showing lines 2-2 of 3. Use offset=2 to read more.]"
+            );
+        }
+    }
+
+    #[test]
+    fn test_read_file_formatted_empty_file() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let workspace = temp_dir.path();
+        fs::write(workspace.join("empty.txt"), "").unwrap();
+
+        let text = read_file_formatted(workspace, "empty.txt", 0, None).unwrap();
+        assert_eq!(text, "");
+    }
+
+    #[test]
+    fn test_read_file_formatted_non_utf8() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let workspace = temp_dir.path();
+        let mut bytes = b"caf\xe9\r\n".to_vec();
+        bytes.extend(b"plain text\n".repeat(5));
+        fs::write(workspace.join("latin1.txt"), bytes).unwrap();
+
+        let text = read_file_formatted(workspace, "latin1.txt", 0, Some(2)).unwrap();
+        assert!(text.starts_with("1\tcaf\u{FFFD}\n2\tplain text\n\n[Showing lines 1-2 of 6."));
+    }
+
+    #[test]
+    fn test_read_file_formatted_binary_and_missing() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let workspace = temp_dir.path();
+        fs::write(workspace.join("blob.bin"), [0u8, 1, 2, 3]).unwrap();
+
+        let result = read_file_formatted(workspace, "blob.bin", 0, None);
+        assert!(matches!(result, Err(ToolError::BinaryContent { size: 4 })));
+
+        let result = read_file_formatted(workspace, "missing.txt", 0, None);
+        assert!(matches!(result, Err(ToolError::PathNotFound(_))));
+    }
+}
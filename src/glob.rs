@@ -0,0 +1,504 @@
+//! Glob expansion confined to the workspace
+//!
+//! This module expands shell-style patterns like `src/**/*.rs` relative to the
+//! workspace, and validates every match with the same canonicalize-and-check
+//! rule as [`resolve_path`](crate::resolve_path).
+//!
+//! Supported syntax, per path component:
+//! - `*` - Any sequence of characters
+//! - `?` - Any single character
+//! - `[abc]`, `[a-z]`, `[!abc]` - Character classes
+//! - `**` - Any number of directories (as a whole component)
+//!
+//! Wildcards don't match a leading `.`, so hidden files are only matched by
+//! patterns that spell out the dot (e.g. `.github/**/*.yml`).
+
+use crate::constants::MAX_GLOB_RESULTS;
+use crate::sandbox::{is_within, Sandbox};
+use crate::ToolError;
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+/// Expand a glob pattern inside the workspace
+///
+/// Returns at most [`MAX_GLOB_RESULTS`] matches; see [`resolve_glob_with_limit`]
+/// to use a different cap.
+///
+/// # Arguments
+///
+/// * `workspace` - The workspace root directory (sandbox boundary)
+/// * `pattern` - The pattern, relative to the workspace or absolute within it
+///
+/// # Returns
+///
+/// * `Ok(Vec<PathBuf>)` - Canonical matching paths, sorted; empty if nothing matches
+/// * `Err(ToolError::PathOutsideWorkspace)` - If the pattern is absolute outside
+///   the workspace or contains `..`
+/// * `Err(ToolError::LimitExceeded)` - If there are more matches than the cap
+///
+/// # Security
+///
+/// Matches that resolve outside the workspace (through symlinks) are dropped.
+/// `**` never descends into symlinked directories, so link cycles can't hang
+/// the expansion.
+///
+/// # Examples
+///
+/// ```rust
+/// use a3s_tools_core::resolve_glob;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let dir = tempfile::tempdir()?;
+/// std::fs::create_dir_all(dir.path().join("src/bin"))?;
+/// std::fs::write(dir.path().join("src/lib.rs"), "")?;
+/// std::fs::write(dir.path().join("src/bin/main.rs"), "")?;
+/// std::fs::write(dir.path().join("README.md"), "")?;
+///
+/// let matches = resolve_glob(dir.path(), "src/**/*.rs")?;
+/// assert_eq!(matches.len(), 2);
+///
+/// assert!(resolve_glob(dir.path(), "../**/*.rs").is_err());
+/// # Ok(())
+/// # }
+/// ```
+pub fn resolve_glob(workspace: &Path, pattern: &str) -> Result<Vec<PathBuf>, ToolError> {
+    resolve_glob_with_limit(workspace, pattern, MAX_GLOB_RESULTS)
+}
+
+/// Expand a glob pattern inside the workspace with a custom result cap
+///
+/// Same as [`resolve_glob`], but fails with [`ToolError::LimitExceeded`] when
+/// there are more than `max_results` matches. The walk stops at the first
+/// match over the cap, so the error reports `max_results + 1` as the count.
+pub fn resolve_glob_with_limit(
+    workspace: &Path,
+    pattern: &str,
+    max_results: usize,
+) -> Result<Vec<PathBuf>, ToolError> {
+    if pattern.is_empty() {
+        return Err(ToolError::invalid_arg("pattern", "cannot be empty"));
+    }
+
+    let sandbox = Sandbox::lenient(workspace);
+    let outside = || ToolError::PathOutsideWorkspace(pattern.to_string());
+
+    let raw = Path::new(pattern);
+    let relative = if raw.is_absolute() {
+        raw.strip_prefix(sandbox.root())
+            .or_else(|_| raw.strip_prefix(workspace))
+            .map_err(|_| outside())?
+    } else {
+        raw
+    };
+
+    let mut segments = Vec::new();
+    for component in relative.components() {
+        match component {
+            Component::Normal(part) => {
+                let part = part
+                    .to_str()
+                    .ok_or_else(|| ToolError::invalid_arg("pattern", "must be valid UTF-8"))?;
+                segments.push(Segment::parse(part));
+            }
+            Component::CurDir => {}
+            _ => return Err(outside()),
+        }
+    }
+
+    let mut matches = Matches {
+        root: sandbox.root(),
+        found: BTreeSet::new(),
+        max_results,
+    };
+    expand(sandbox.root(), &segments, &mut matches);
+
+    if matches.is_full() {
+        return Err(ToolError::limit_exceeded(
+            "glob matches",
+            max_results,
+            matches.found.len(),
+        ));
+    }
+
+    Ok(matches.found.into_iter().collect())
+}
+
+/// Validated matches collected during expansion
+struct Matches<'a> {
+    root: &'a Path,
+    /// Canonical paths inside `root`
+    found: BTreeSet<PathBuf>,
+    max_results: usize,
+}
+
+impl Matches<'_> {
+    /// Canonicalize `candidate` and keep it if it stays inside the workspace
+    fn add(&mut self, candidate: &Path) {
+        if self.is_full() {
+            return;
+        }
+        if let Ok(canonical) = candidate.canonicalize() {
+            if is_within(&canonical, self.root) {
+                self.found.insert(canonical);
+            }
+        }
+    }
+
+    /// Whether the cap has been exceeded, so the walk can stop
+    fn is_full(&self) -> bool {
+        self.found.len() > self.max_results
+    }
+}
+
+/// One component of a parsed pattern
+enum Segment<'a> {
+    /// No wildcards; matched by name without listing the directory
+    Literal(&'a str),
+    /// Contains `*`, `?` or `[...]`
+    Wildcard(&'a str),
+    /// `**`
+    Recursive,
+}
+
+impl<'a> Segment<'a> {
+    fn parse(part: &'a str) -> Self {
+        if part == "**" {
+            Segment::Recursive
+        } else if part.contains(['*', '?', '[']) {
+            Segment::Wildcard(part)
+        } else {
+            Segment::Literal(part)
+        }
+    }
+}
+
+/// Collect every path under `dir` matching `segments`, until `out` is full
+fn expand(dir: &Path, segments: &[Segment], out: &mut Matches) {
+    if out.is_full() {
+        return;
+    }
+    let Some((segment, rest)) = segments.split_first() else {
+        out.add(dir);
+        return;
+    };
+
+    match segment {
+        Segment::Literal(name) => {
+            let child = dir.join(name);
+            if rest.is_empty() {
+                if child.symlink_metadata().is_ok() {
+                    out.add(&child);
+                }
+            } else if child.is_dir() {
+                expand(&child, rest, out);
+            }
+        }
+        Segment::Wildcard(pattern) => {
+            for (name, path, _) in list_dir(dir) {
+                if !name.starts_with('.') && matches_component(pattern, &name) {
+                    if rest.is_empty() {
+                        out.add(&path);
+                    } else if path.is_dir() {
+                        expand(&path, rest, out);
+                    }
+                }
+            }
+        }
+        Segment::Recursive => {
+            // Zero directories
+            expand(dir, rest, out);
+            // One or more, never following symlinked directories
+            for (name, path, is_real_dir) in list_dir(dir) {
+                if is_real_dir && !name.starts_with('.') {
+                    expand(&path, segments, out);
+                }
+            }
+        }
+    }
+}
+
+/// List `(name, path, is_dir_without_following_symlinks)` for entries of `dir`
+fn list_dir(dir: &Path) -> Vec<(String, PathBuf, bool)> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    entries
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let name = entry.file_name().into_string().ok()?;
+            let is_dir = entry.file_type().map(|t| t.is_dir()).unwrap_or(false);
+            Some((name, entry.path(), is_dir))
+        })
+        .collect()
+}
+
+/// Match a single path component against a wildcard pattern
+fn matches_component(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    matches_from(&pattern, &name)
+}
+
+/// Iterative wildcard match
+///
+/// On a mismatch only the most recent `*` is retried, one character further
+/// along, instead of backtracking into every earlier `*`. That's sufficient
+/// because an earlier `*` absorbing more can't help a later one, and keeps the
+/// cost at O(pattern × name) however many stars the pattern has.
+fn matches_from(pattern: &[char], name: &[char]) -> bool {
+    let (mut p, mut n) = (0, 0);
+    // Pattern index after the last `*`, and the name index it's matched up to
+    let mut star: Option<(usize, usize)> = None;
+
+    while n < name.len() {
+        if pattern.get(p) == Some(&'*') {
+            p += 1;
+            star = Some((p, n));
+        } else if let Some(len) = match_one(&pattern[p..], name[n]) {
+            p += len;
+            n += 1;
+        } else if let Some((after_star, matched)) = star {
+            // Let the last `*` swallow one more character and retry
+            p = after_star;
+            n = matched + 1;
+            star = Some((after_star, n));
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Match `c` against the token at the start of `pattern` (anything but `*`),
+/// returning the token's length
+fn match_one(pattern: &[char], c: char) -> Option<usize> {
+    match pattern.split_first()? {
+        ('?', _) => Some(1),
+        ('[', rest) => match parse_class(rest) {
+            Some((class, after)) => class.matches(c).then_some(pattern.len() - after.len()),
+            // Unterminated class: treat '[' literally
+            None => (c == '[').then_some(1),
+        },
+        (&p, _) => (p == c).then_some(1),
+    }
+}
+
+/// A parsed `[...]` character class
+struct CharClass<'a> {
+    negated: bool,
+    members: &'a [char],
+}
+
+impl CharClass<'_> {
+    fn matches(&self, c: char) -> bool {
+        let members = self.members;
+        let mut i = 0;
+        let mut found = false;
+        while i < members.len() {
+            if i + 2 < members.len() && members[i + 1] == '-' {
+                found |= members[i] <= c && c <= members[i + 2];
+                i += 3;
+            } else {
+                found |= members[i] == c;
+                i += 1;
+            }
+        }
+        found != self.negated
+    }
+}
+
+/// Parse a `[...]` class body (after the `[`), returning it and the remaining pattern
+fn parse_class(pattern: &[char]) -> Option<(CharClass<'_>, &[char])> {
+    let (negated, start) = match pattern.first() {
+        Some('!') | Some('^') => (true, 1),
+        _ => (false, 0),
+    };
+    // A ']' right after the opening bracket is a literal member
+    let close = (start + 1..pattern.len()).find(|&i| pattern[i] == ']')?;
+
+    let class = CharClass {
+        negated,
+        members: &pattern[start..close],
+    };
+    Some((class, &pattern[close + 1..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup() -> tempfile::TempDir {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("src/nested/deep")).unwrap();
+        fs::create_dir_all(root.join(".hidden")).unwrap();
+        fs::write(root.join("src/lib.rs"), "").unwrap();
+        fs::write(root.join("src/nested/mod.rs"), "").unwrap();
+        fs::write(root.join("src/nested/deep/leaf.rs"), "").unwrap();
+        fs::write(root.join("src/nested/notes.md"), "").unwrap();
+        fs::write(root.join(".hidden/secret.rs"), "").unwrap();
+        fs::write(root.join("Cargo.toml"), "").unwrap();
+        temp_dir
+    }
+
+    fn relative(workspace: &Path, paths: &[PathBuf]) -> Vec<String> {
+        let root = workspace.canonicalize().unwrap();
+        paths
+            .iter()
+            .map(|p| {
+                p.strip_prefix(&root)
+                    .unwrap()
+                    .to_string_lossy()
+                    .into_owned()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_matches_component() {
+        assert!(matches_component("*.rs", "main.rs"));
+        assert!(!matches_component("*.rs", "main.rs.bak"));
+        assert!(matches_component("ma?n.rs", "main.rs"));
+        assert!(matches_component("[mn]ain.rs", "nain.rs"));
+        assert!(matches_component("file[0-9].txt", "file7.txt"));
+        assert!(!matches_component("file[!0-9].txt", "file7.txt"));
+        assert!(matches_component("[]]", "]"));
+        assert!(matches_component("a[b", "a[b"));
+        assert!(matches_component("*", ""));
+        assert!(matches_component("a*b*c", "aXbYbZc"));
+        assert!(matches_component("*.tar.*", "x.tar.gz"));
+        assert!(!matches_component("a*b", "aXbY"));
+        assert!(matches_component("*[0-9]", "v12"));
+    }
+
+    #[test]
+    fn test_matches_component_many_stars() {
+        // Exponential with naive backtracking; must return immediately
+        let name = "a".repeat(40);
+        let pattern = format!("{}b", "*a".repeat(30));
+        let start = std::time::Instant::now();
+        assert!(!matches_component(&pattern, &name));
+        assert!(matches_component(&"*a".repeat(30), &name));
+        assert!(start.elapsed() < std::time::Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_resolve_glob_nested() {
+        let temp_dir = setup();
+        let workspace = temp_dir.path();
+
+        let matches = resolve_glob(workspace, "src/**/*.rs").unwrap();
+        assert_eq!(
+            relative(workspace, &matches),
+            vec!["src/lib.rs", "src/nested/deep/leaf.rs", "src/nested/mod.rs"]
+        );
+
+        let matches = resolve_glob(workspace, "src/*/*.md").unwrap();
+        assert_eq!(relative(workspace, &matches), vec!["src/nested/notes.md"]);
+
+        // Wildcards skip hidden entries; an explicit dot matches them
+        let matches = resolve_glob(workspace, "**/*.rs").unwrap();
+        assert_eq!(matches.len(), 3);
+        let matches = resolve_glob(workspace, ".hidden/*.rs").unwrap();
+        assert_eq!(relative(workspace, &matches), vec![".hidden/secret.rs"]);
+    }
+
+    #[test]
+    fn test_resolve_glob_absolute_within_workspace() {
+        let temp_dir = setup();
+        let workspace = temp_dir.path();
+
+        let pattern = format!("{}/src/*.rs", workspace.display());
+        let matches = resolve_glob(workspace, &pattern).unwrap();
+        assert_eq!(relative(workspace, &matches), vec!["src/lib.rs"]);
+    }
+
+    #[test]
+    fn test_resolve_glob_zero_matches() {
+        let temp_dir = setup();
+        let workspace = temp_dir.path();
+
+        assert!(resolve_glob(workspace, "**/*.py").unwrap().is_empty());
+        assert!(resolve_glob(workspace, "missing/dir/*.rs")
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_resolve_glob_rejects_escapes() {
+        let temp_dir = setup();
+        let workspace = temp_dir.path();
+
+        let result = resolve_glob(workspace, "../*");
+        assert!(matches!(result, Err(ToolError::PathOutsideWorkspace(_))));
+
+        let result = resolve_glob(workspace, "src/../../*");
+        assert!(matches!(result, Err(ToolError::PathOutsideWorkspace(_))));
+
+        let result = resolve_glob(workspace, "/etc/*");
+        assert!(matches!(result, Err(ToolError::PathOutsideWorkspace(_))));
+    }
+
+    #[test]
+    fn test_resolve_glob_limit() {
+        let temp_dir = setup();
+        let workspace = temp_dir.path();
+
+        let result = resolve_glob_with_limit(workspace, "src/**/*.rs", 2);
+        assert!(matches!(
+            result,
+            Err(ToolError::LimitExceeded {
+                limit: 2,
+                actual: 3,
+                ..
+            })
+        ));
+        assert!(resolve_glob_with_limit(workspace, "src/**/*.rs", 3).is_ok());
+
+        // The walk stops one past the cap rather than counting everything
+        for i in 0..50 {
+            fs::write(workspace.join(format!("src/gen{i}.rs")), "").unwrap();
+        }
+        let result = resolve_glob_with_limit(workspace, "src/**/*.rs", 5);
+        assert!(matches!(
+            result,
+            Err(ToolError::LimitExceeded {
+                limit: 5,
+                actual: 6,
+                ..
+            })
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_resolve_glob_drops_symlinks_outside() {
+        let temp_dir = setup();
+        let workspace = temp_dir.path();
+        let outside = tempfile::tempdir().unwrap();
+        fs::write(outside.path().join("secret.rs"), "").unwrap();
+
+        std::os::unix::fs::symlink(outside.path(), workspace.join("src/escape")).unwrap();
+        std::os::unix::fs::symlink(
+            outside.path().join("secret.rs"),
+            workspace.join("src/linked.rs"),
+        )
+        .unwrap();
+        // Link cycle back to the workspace root
+        std::os::unix::fs::symlink(workspace, workspace.join("src/loop")).unwrap();
+
+        let matches = resolve_glob(workspace, "src/**/*.rs").unwrap();
+        assert_eq!(
+            relative(workspace, &matches),
+            vec!["src/lib.rs", "src/nested/deep/leaf.rs", "src/nested/mod.rs"]
+        );
+
+        // Explicitly naming the symlinked directory still can't escape
+        assert!(resolve_glob(workspace, "src/escape/*.rs")
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -2,9 +2,20 @@
 //!
 //! This crate provides common functionality for tool implementations in the A3S ecosystem:
 //! - **Sandbox path resolution and validation** - Ensures all file operations stay within workspace boundaries
+//!   (use [`Sandbox`] to resolve many paths against one workspace)
+//! - **Glob expansion** - Patterns like `src/**/*.rs` expanded and validated inside the workspace
+//! - **Directory listing** - [`list_directory`] walks the workspace without following symlinks,
+//!   optionally honoring `.gitignore` files
 //! - **Constants for output limits** - Predefined limits for output size, line length, and timeouts
 //! - **Error types** - Comprehensive error handling with [`ToolError`]
 //! - **Output formatting utilities** - Line numbering and output truncation helpers
+//!   (plus unified diffs with the `diff` feature)
+//! - **Sandboxed file helpers** - Formatted reads and atomic writes that stay within the workspace
+//! - **Command execution** - [`run_command`] with a sandboxed working directory, a deadline,
+//!   and output capped while it is captured
+//! - **Timeouts** - [`validate_timeout`] applies the default and maximum, and
+//!   [`with_timeout_blocking`] (or `with_timeout` with the `async` feature) enforces it
+//! - **Configurable limits** - [`OutputLimits`] for per-tool budgets, loadable from the environment
 //!
 //! # Examples
 //!
@@ -49,13 +60,45 @@
 //! - Canonicalizes paths to handle symlinks (e.g., `/var` → `/private/var` on macOS)
 //! - Rejects absolute paths outside workspace
 //! - Rejects relative paths that escape workspace (e.g., `../../etc/passwd`)
+//! - Reports symlink loops as [`ToolError::SymlinkLoop`], following at most [`MAX_SYMLINK_DEPTH`] links
+//! - Optionally refuses any symlink component with [`ResolveOptions::follow_symlinks`] set to `false`
+//! - Normalizes Windows verbatim prefixes (`\\?\C:\`), drive-letter case and separators before comparing
 
 mod constants;
 mod error;
+mod exec;
+mod fs;
+mod glob;
+mod limits;
+mod list;
 mod output;
 mod sandbox;
+mod timeout;
 
 pub use constants::*;
-pub use error::ToolError;
-pub use output::{format_line_numbered, truncate_output};
-pub use sandbox::{resolve_path, resolve_path_for_write};
+pub use error::{ErrorCategory, ToolError};
+pub use exec::{run_command, CommandOutput};
+pub use fs::{
+    check_file_size, read_file_formatted, read_file_formatted_with_budget, write_file_atomic,
+};
+pub use glob::{resolve_glob, resolve_glob_with_limit};
+pub use limits::{OutputLimits, ENV_MAX_LINE_LENGTH, ENV_MAX_OUTPUT_SIZE, ENV_MAX_READ_LINES};
+pub use list::{list_directory, DirEntryInfo, DirectoryListing, EntryKind, ListOptions};
+pub use output::{
+    detect_line_ending, format_bytes, format_count, format_file_content, format_line_numbered,
+    format_line_numbered_range, format_line_numbered_range_with_limits,
+    format_line_numbered_with_limits, is_probably_binary, truncate_output, truncate_output_lines,
+    truncate_output_tail, truncate_output_with_info, truncate_output_with_limits,
+    truncate_output_with_mode, FormattedOutput, LineEnding, TruncateMode, TruncationInfo,
+};
+#[cfg(feature = "diff")]
+pub use output::{generate_diff, generate_diff_stats, generate_diff_with_limits, DiffStats};
+pub use sandbox::{
+    relativize, resolve_path, resolve_path_for_write, resolve_path_for_write_with_options,
+    resolve_path_with_options, resolve_paths, resolve_paths_all_or_nothing,
+    resolve_paths_for_write, resolve_paths_for_write_all_or_nothing, ResolveOptions, Sandbox,
+    SandboxBuilder,
+};
+#[cfg(feature = "async")]
+pub use timeout::with_timeout;
+pub use timeout::{validate_timeout, with_timeout_blocking};
@@ -0,0 +1,151 @@
+//! Configurable output limits
+//!
+//! This module provides [`OutputLimits`], a runtime-configurable alternative to
+//! the compile-time constants in [`crate::constants`], for tools that need
+//! different budgets (e.g. a bash tool allowing more output than a read tool).
+
+use crate::constants::{MAX_LINE_LENGTH, MAX_OUTPUT_SIZE, MAX_READ_LINES};
+
+/// Environment variable overriding [`OutputLimits::max_output_size`]
+pub const ENV_MAX_OUTPUT_SIZE: &str = "A3S_MAX_OUTPUT_SIZE";
+
+/// Environment variable overriding [`OutputLimits::max_line_length`]
+pub const ENV_MAX_LINE_LENGTH: &str = "A3S_MAX_LINE_LENGTH";
+
+/// Environment variable overriding [`OutputLimits::max_read_lines`]
+pub const ENV_MAX_READ_LINES: &str = "A3S_MAX_READ_LINES";
+
+/// Output limits used by the formatting and truncation helpers
+///
+/// The [`Default`] implementation matches the crate constants
+/// ([`MAX_OUTPUT_SIZE`], [`MAX_LINE_LENGTH`], [`MAX_READ_LINES`]).
+///
+/// # Examples
+///
+/// ```rust
+/// use a3s_tools_core::{truncate_output_with_limits, OutputLimits};
+///
+/// let limits = OutputLimits {
+///     max_output_size: 200 * 1024,
+///     ..OutputLimits::default()
+/// };
+///
+/// let output = "x".repeat(150_000);
+/// assert_eq!(truncate_output_with_limits(&output, &limits), output);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutputLimits {
+    /// Maximum output size in bytes before truncation
+    pub max_output_size: usize,
+    /// Maximum line length in characters before truncation
+    pub max_line_length: usize,
+    /// Maximum lines to read from a file
+    pub max_read_lines: usize,
+}
+
+impl Default for OutputLimits {
+    fn default() -> Self {
+        Self {
+            max_output_size: MAX_OUTPUT_SIZE,
+            max_line_length: MAX_LINE_LENGTH,
+            max_read_lines: MAX_READ_LINES,
+        }
+    }
+}
+
+impl OutputLimits {
+    /// Load limits from environment variables, falling back to defaults
+    ///
+    /// Reads [`ENV_MAX_OUTPUT_SIZE`], [`ENV_MAX_LINE_LENGTH`] and
+    /// [`ENV_MAX_READ_LINES`]. A variable that is unset, not a positive
+    /// integer, or (for line length) too short to hold the `...` marker is
+    /// ignored and the default for that field is used instead.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use a3s_tools_core::OutputLimits;
+    ///
+    /// // With no A3S_* variables set, this equals the defaults
+    /// let limits = OutputLimits::from_env();
+    /// assert!(limits.max_output_size > 0);
+    /// ```
+    pub fn from_env() -> Self {
+        Self::from_lookup(|name| std::env::var(name).ok())
+    }
+
+    /// Build limits from an arbitrary variable lookup
+    fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> Self {
+        let defaults = Self::default();
+        let read = |name: &str, min: usize, default: usize| {
+            lookup(name)
+                .and_then(|value| value.trim().parse::<usize>().ok())
+                .filter(|&value| value >= min)
+                .unwrap_or(default)
+        };
+
+        Self {
+            max_output_size: read(ENV_MAX_OUTPUT_SIZE, 1, defaults.max_output_size),
+            // Must leave room for at least one character plus "..."
+            max_line_length: read(ENV_MAX_LINE_LENGTH, 4, defaults.max_line_length),
+            max_read_lines: read(ENV_MAX_READ_LINES, 1, defaults.max_read_lines),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn lookup(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        move |name| vars.get(name).cloned()
+    }
+
+    #[test]
+    fn test_default_matches_constants() {
+        let limits = OutputLimits::default();
+        assert_eq!(limits.max_output_size, MAX_OUTPUT_SIZE);
+        assert_eq!(limits.max_line_length, MAX_LINE_LENGTH);
+        assert_eq!(limits.max_read_lines, MAX_READ_LINES);
+    }
+
+    #[test]
+    fn test_from_lookup_overrides() {
+        let limits = OutputLimits::from_lookup(lookup(&[
+            (ENV_MAX_OUTPUT_SIZE, "204800"),
+            (ENV_MAX_LINE_LENGTH, " 500 "),
+            (ENV_MAX_READ_LINES, "100"),
+        ]));
+
+        assert_eq!(
+            limits,
+            OutputLimits {
+                max_output_size: 204_800,
+                max_line_length: 500,
+                max_read_lines: 100,
+            }
+        );
+    }
+
+    #[test]
+    fn test_from_lookup_invalid_values_fall_back() {
+        let limits = OutputLimits::from_lookup(lookup(&[
+            (ENV_MAX_OUTPUT_SIZE, "lots"),
+            (ENV_MAX_LINE_LENGTH, "3"),
+            (ENV_MAX_READ_LINES, "0"),
+        ]));
+
+        assert_eq!(limits, OutputLimits::default());
+    }
+
+    #[test]
+    fn test_from_lookup_unset() {
+        let limits = OutputLimits::from_lookup(lookup(&[]));
+        assert_eq!(limits, OutputLimits::default());
+    }
+}
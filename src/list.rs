@@ -0,0 +1,472 @@
+//! Directory listing confined to the workspace
+//!
+//! This module provides [`list_directory`], the shared walker behind `ls`-,
+//! `tree`- and glob-style tools. Entries are reported relative to the workspace
+//! root, in depth-first order with siblings sorted by name.
+//!
+//! With the `gitignore` feature enabled, `.gitignore` and `.ignore` files are
+//! honored using the [`ignore`](https://docs.rs/ignore) crate's matcher.
+
+use crate::constants::MAX_LIST_ENTRIES;
+use crate::sandbox::Sandbox;
+use crate::ToolError;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Options for [`list_directory`]
+///
+/// # Examples
+///
+/// ```rust
+/// use a3s_tools_core::ListOptions;
+///
+/// // Walk the whole tree, including dotfiles
+/// let options = ListOptions {
+///     max_depth: None,
+///     include_hidden: true,
+///     ..Default::default()
+/// };
+/// assert!(options.respect_ignore_files);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ListOptions {
+    /// How many levels to descend; `Some(1)` lists only direct children, `None` is unlimited
+    pub max_depth: Option<usize>,
+
+    /// Include entries whose name starts with `.`
+    pub include_hidden: bool,
+
+    /// Skip entries matched by `.gitignore`/`.ignore` files
+    ///
+    /// Ignore files in the workspace root and every directory down to (and
+    /// below) the listed one are applied. Has no effect unless the `gitignore`
+    /// feature is enabled.
+    pub respect_ignore_files: bool,
+
+    /// Maximum number of entries to return before setting
+    /// [`DirectoryListing::truncated`]
+    pub max_entries: usize,
+}
+
+impl Default for ListOptions {
+    fn default() -> Self {
+        Self {
+            max_depth: Some(1),
+            include_hidden: false,
+            respect_ignore_files: true,
+            max_entries: MAX_LIST_ENTRIES,
+        }
+    }
+}
+
+/// Kind of a listed entry, as seen without following symlinks
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryKind {
+    /// Regular file
+    File,
+    /// Directory
+    Dir,
+    /// Symbolic link (never followed, wherever it points)
+    Symlink,
+    /// Anything else (sockets, FIFOs, devices)
+    Other,
+}
+
+/// One entry returned by [`list_directory`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirEntryInfo {
+    /// Path relative to the workspace root
+    pub path: PathBuf,
+    /// Entry kind
+    pub file_type: EntryKind,
+    /// Size in bytes for files, 0 for everything else
+    pub size: u64,
+}
+
+/// Result of [`list_directory`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirectoryListing {
+    /// Entries in depth-first order, siblings sorted by name
+    pub entries: Vec<DirEntryInfo>,
+    /// Whether more entries were found than [`ListOptions::max_entries`]
+    pub truncated: bool,
+}
+
+/// List a directory inside the workspace
+///
+/// # Arguments
+///
+/// * `workspace` - The workspace root directory (sandbox boundary)
+/// * `path` - The directory to list, relative to the workspace or absolute within it
+/// * `options` - Depth, hidden-file, ignore-file and entry-count settings
+///
+/// # Returns
+///
+/// * `Ok(DirectoryListing)` - Entries with workspace-relative paths
+/// * `Err(ToolError::PathNotFound)` - If the directory doesn't exist
+/// * `Err(ToolError::PathOutsideWorkspace)` - If the directory is outside the workspace
+/// * `Err(ToolError::InvalidArgument)` - If the path is not a directory
+///
+/// # Security
+///
+/// Symlinks are listed as [`EntryKind::Symlink`] and never followed, so a
+/// link pointing outside the workspace can't leak its target's contents and
+/// link cycles can't hang the walk. Unreadable subdirectories are skipped.
+///
+/// # Examples
+///
+/// ```rust
+/// use a3s_tools_core::{list_directory, EntryKind, ListOptions};
+/// use std::path::Path;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let dir = tempfile::tempdir()?;
+/// std::fs::create_dir_all(dir.path().join("src"))?;
+/// std::fs::write(dir.path().join("src/lib.rs"), "fn main() {}\n")?;
+///
+/// let listing = list_directory(dir.path(), "src", ListOptions::default())?;
+/// assert_eq!(listing.entries[0].path, Path::new("src/lib.rs"));
+/// assert_eq!(listing.entries[0].file_type, EntryKind::File);
+/// assert_eq!(listing.entries[0].size, 13);
+/// assert!(!listing.truncated);
+/// # Ok(())
+/// # }
+/// ```
+pub fn list_directory(
+    workspace: &Path,
+    path: &str,
+    options: ListOptions,
+) -> Result<DirectoryListing, ToolError> {
+    let sandbox = Sandbox::lenient(workspace);
+    let dir = sandbox.resolve(path)?;
+    if !dir.is_dir() {
+        return Err(ToolError::invalid_arg(
+            "path",
+            format!("'{}' is not a directory", path),
+        ));
+    }
+
+    let mut walker = Walker {
+        root: sandbox.root(),
+        options: &options,
+        rules: IgnoreRules::default(),
+        listing: DirectoryListing {
+            entries: Vec::new(),
+            truncated: false,
+        },
+    };
+
+    if options.respect_ignore_files {
+        // Rules from the workspace root down to the listed directory apply too
+        if let Ok(relative) = dir.strip_prefix(sandbox.root()) {
+            let mut ancestor = sandbox.root().to_path_buf();
+            walker.rules.enter(&ancestor);
+            for component in relative.components() {
+                ancestor.push(component);
+                walker.rules.enter(&ancestor);
+            }
+        }
+    }
+
+    walker.walk(&dir, 1);
+    Ok(walker.listing)
+}
+
+/// Depth-first walk state
+struct Walker<'a> {
+    root: &'a Path,
+    options: &'a ListOptions,
+    rules: IgnoreRules,
+    listing: DirectoryListing,
+}
+
+impl Walker<'_> {
+    /// Append the entries of `dir`; returns `false` once the entry cap is hit
+    fn walk(&mut self, dir: &Path, depth: usize) -> bool {
+        let Ok(read) = fs::read_dir(dir) else {
+            return true;
+        };
+        let mut children: Vec<fs::DirEntry> = read.filter_map(Result::ok).collect();
+        children.sort_by_key(|entry| entry.file_name());
+
+        for entry in children {
+            let name = entry.file_name();
+            if !self.options.include_hidden && name.to_string_lossy().starts_with('.') {
+                continue;
+            }
+
+            let Ok(metadata) = entry.path().symlink_metadata() else {
+                continue;
+            };
+            let file_type = metadata.file_type();
+            let kind = if file_type.is_symlink() {
+                EntryKind::Symlink
+            } else if file_type.is_dir() {
+                EntryKind::Dir
+            } else if file_type.is_file() {
+                EntryKind::File
+            } else {
+                EntryKind::Other
+            };
+
+            let path = entry.path();
+            if self.options.respect_ignore_files
+                && self.rules.is_ignored(&path, kind == EntryKind::Dir)
+            {
+                continue;
+            }
+
+            if self.listing.entries.len() >= self.options.max_entries {
+                self.listing.truncated = true;
+                return false;
+            }
+
+            let relative = path.strip_prefix(self.root).unwrap_or(&path).to_path_buf();
+            self.listing.entries.push(DirEntryInfo {
+                path: relative,
+                file_type: kind,
+                size: if kind == EntryKind::File {
+                    metadata.len()
+                } else {
+                    0
+                },
+            });
+
+            let descend =
+                kind == EntryKind::Dir && self.options.max_depth.is_none_or(|max| depth < max);
+            if descend {
+                let entered = if self.options.respect_ignore_files {
+                    self.rules.enter(&path)
+                } else {
+                    0
+                };
+                let keep_going = self.walk(&path, depth + 1);
+                self.rules.leave(entered);
+                if !keep_going {
+                    return false;
+                }
+            }
+        }
+
+        true
+    }
+}
+
+/// Stack of ignore matchers for the directories currently being walked
+#[derive(Default)]
+struct IgnoreRules {
+    #[cfg(feature = "gitignore")]
+    matchers: Vec<ignore::gitignore::Gitignore>,
+}
+
+#[cfg(feature = "gitignore")]
+impl IgnoreRules {
+    /// Load the ignore files in `dir`, returning how many matchers were pushed
+    fn enter(&mut self, dir: &Path) -> usize {
+        let mut pushed = 0;
+        // `.ignore` is pushed last so it takes precedence over `.gitignore`
+        for name in [".gitignore", ".ignore"] {
+            let file = dir.join(name);
+            if file.is_file() {
+                // Invalid lines are skipped; the valid ones still apply
+                let (matcher, _partial_error) = ignore::gitignore::Gitignore::new(&file);
+                self.matchers.push(matcher);
+                pushed += 1;
+            }
+        }
+        pushed
+    }
+
+    fn leave(&mut self, count: usize) {
+        self.matchers.truncate(self.matchers.len() - count);
+    }
+
+    /// The innermost matcher with an opinion (ignore or `!` whitelist) wins
+    fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        self.matchers
+            .iter()
+            .rev()
+            .map(|matcher| matcher.matched(path, is_dir))
+            .find(|m| !m.is_none())
+            .is_some_and(|m| m.is_ignore())
+    }
+}
+
+#[cfg(not(feature = "gitignore"))]
+impl IgnoreRules {
+    fn enter(&mut self, _dir: &Path) -> usize {
+        0
+    }
+
+    fn leave(&mut self, _count: usize) {}
+
+    fn is_ignored(&self, _path: &Path, _is_dir: bool) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup() -> tempfile::TempDir {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("src/nested")).unwrap();
+        fs::create_dir_all(root.join("target/debug")).unwrap();
+        fs::create_dir_all(root.join(".git")).unwrap();
+        fs::write(root.join("Cargo.toml"), "[package]\n").unwrap();
+        fs::write(root.join("src/lib.rs"), "").unwrap();
+        fs::write(root.join("src/nested/mod.rs"), "").unwrap();
+        fs::write(root.join("src/nested/debug.log"), "").unwrap();
+        fs::write(root.join("target/debug/app"), "").unwrap();
+        fs::write(root.join(".env"), "").unwrap();
+        fs::write(root.join(".gitignore"), "target/\n*.log\n").unwrap();
+        temp_dir
+    }
+
+    fn paths(listing: &DirectoryListing) -> Vec<String> {
+        listing
+            .entries
+            .iter()
+            .map(|e| e.path.to_string_lossy().into_owned())
+            .collect()
+    }
+
+    fn unlimited() -> ListOptions {
+        ListOptions {
+            max_depth: None,
+            respect_ignore_files: false,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_list_directory_default_depth() {
+        let temp_dir = setup();
+        let listing = list_directory(temp_dir.path(), ".", ListOptions::default()).unwrap();
+
+        #[cfg(feature = "gitignore")]
+        assert_eq!(paths(&listing), vec!["Cargo.toml", "src"]);
+        #[cfg(not(feature = "gitignore"))]
+        assert_eq!(paths(&listing), vec!["Cargo.toml", "src", "target"]);
+
+        assert_eq!(listing.entries[0].file_type, EntryKind::File);
+        assert_eq!(listing.entries[0].size, 10);
+        assert_eq!(listing.entries[1].file_type, EntryKind::Dir);
+        assert_eq!(listing.entries[1].size, 0);
+        assert!(!listing.truncated);
+    }
+
+    #[test]
+    fn test_list_directory_recursive_and_hidden() {
+        let temp_dir = setup();
+        let workspace = temp_dir.path();
+
+        let listing = list_directory(workspace, "src", unlimited()).unwrap();
+        assert_eq!(
+            paths(&listing),
+            vec![
+                "src/lib.rs",
+                "src/nested",
+                "src/nested/debug.log",
+                "src/nested/mod.rs"
+            ]
+        );
+
+        let options = ListOptions {
+            include_hidden: true,
+            ..unlimited()
+        };
+        let listing = list_directory(workspace, ".", options).unwrap();
+        assert!(paths(&listing).contains(&".env".to_string()));
+        assert!(paths(&listing).contains(&".git".to_string()));
+        assert!(paths(&listing).contains(&"target/debug/app".to_string()));
+    }
+
+    #[cfg(feature = "gitignore")]
+    #[test]
+    fn test_list_directory_respects_ignore_files() {
+        let temp_dir = setup();
+        let workspace = temp_dir.path();
+        fs::write(workspace.join("src/.ignore"), "!debug.log\n").unwrap();
+        fs::write(workspace.join("src/nested/trace.log"), "").unwrap();
+
+        let options = ListOptions {
+            max_depth: None,
+            ..Default::default()
+        };
+        let listing = list_directory(workspace, ".", options.clone()).unwrap();
+        let listed = paths(&listing);
+        assert!(!listed.iter().any(|p| p.starts_with("target")));
+        // `.ignore` in src/ whitelists debug.log; other logs stay ignored
+        assert!(listed.contains(&"src/nested/debug.log".to_string()));
+        assert!(!listed.contains(&"src/nested/trace.log".to_string()));
+
+        // Rules from the workspace root apply when listing a subdirectory
+        let listing = list_directory(workspace, "src/nested", options).unwrap();
+        assert_eq!(
+            paths(&listing),
+            vec!["src/nested/debug.log", "src/nested/mod.rs"]
+        );
+    }
+
+    #[test]
+    fn test_list_directory_truncated() {
+        let temp_dir = setup();
+        let options = ListOptions {
+            max_entries: 2,
+            ..unlimited()
+        };
+        let listing = list_directory(temp_dir.path(), ".", options).unwrap();
+        assert_eq!(paths(&listing), vec!["Cargo.toml", "src"]);
+        assert!(listing.truncated);
+
+        // Exactly at the cap is not truncated
+        let options = ListOptions {
+            max_entries: 2,
+            ..unlimited()
+        };
+        let listing = list_directory(temp_dir.path(), "src/nested", options).unwrap();
+        assert_eq!(listing.entries.len(), 2);
+        assert!(!listing.truncated);
+    }
+
+    #[test]
+    fn test_list_directory_rejects_bad_paths() {
+        let temp_dir = setup();
+        let workspace = temp_dir.path();
+
+        let result = list_directory(workspace, "../", ListOptions::default());
+        assert!(matches!(result, Err(ToolError::PathOutsideWorkspace(_))));
+
+        let result = list_directory(workspace, "missing", ListOptions::default());
+        assert!(matches!(result, Err(ToolError::PathNotFound(_))));
+
+        let result = list_directory(workspace, "Cargo.toml", ListOptions::default());
+        assert!(matches!(result, Err(ToolError::InvalidArgument { .. })));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_list_directory_does_not_follow_symlinks() {
+        let temp_dir = setup();
+        let workspace = temp_dir.path();
+        let outside = tempfile::tempdir().unwrap();
+        fs::write(outside.path().join("secret.txt"), "secret").unwrap();
+
+        std::os::unix::fs::symlink(outside.path(), workspace.join("src/escape")).unwrap();
+        std::os::unix::fs::symlink(workspace.join("src"), workspace.join("src/loop")).unwrap();
+
+        let listing = list_directory(workspace, "src", unlimited()).unwrap();
+        let escape = listing
+            .entries
+            .iter()
+            .find(|e| e.path == Path::new("src/escape"))
+            .unwrap();
+        assert_eq!(escape.file_type, EntryKind::Symlink);
+        assert_eq!(escape.size, 0);
+        assert!(!paths(&listing).iter().any(|p| p.contains("secret")));
+        assert!(!paths(&listing).iter().any(|p| p.starts_with("src/loop/")));
+    }
+}
@@ -3,12 +3,25 @@
 //! This module provides functions for formatting tool output with line numbers
 //! and truncating large outputs to prevent memory exhaustion.
 
-use crate::constants::{MAX_LINE_LENGTH, MAX_OUTPUT_SIZE};
+use crate::limits::OutputLimits;
+use crate::ToolError;
+use std::borrow::Cow;
+
+/// Number of leading bytes inspected by [`is_probably_binary`] (same as git)
+const BINARY_SAMPLE_SIZE: usize = 8000;
 
 /// Format content with line numbers
 ///
 /// Adds line numbers to each line of content, similar to `cat -n`.
-/// Lines longer than [`MAX_LINE_LENGTH`] are truncated with "..." appended.
+/// Lines longer than [`MAX_LINE_LENGTH`](crate::MAX_LINE_LENGTH) characters are truncated with "..." appended.
+///
+/// A leading UTF-8 byte order mark and the `\r` of CRLF line endings are
+/// stripped, so Windows files number the same as Unix ones and downstream
+/// string matching sees clean lines. Use [`detect_line_ending`] to learn the
+/// original style before writing the file back.
+///
+/// Truncation is performed on character boundaries, so this never panics on
+/// valid UTF-8 input, including lines made of multi-byte characters.
 ///
 /// # Arguments
 ///
@@ -47,7 +60,300 @@ use crate::constants::{MAX_LINE_LENGTH, MAX_OUTPUT_SIZE};
 /// assert!(formatted.contains("12\tline2"));
 /// ```
 pub fn format_line_numbered(content: &str, offset: usize) -> String {
-    let lines: Vec<&str> = content.lines().collect();
+    format_line_numbered_with_limits(content, offset, &OutputLimits::default())
+}
+
+/// Format content with line numbers using custom limits
+///
+/// Same as [`format_line_numbered`], but truncates lines longer than
+/// `limits.max_line_length` characters instead of [`MAX_LINE_LENGTH`](crate::MAX_LINE_LENGTH).
+///
+/// # Examples
+///
+/// ```rust
+/// use a3s_tools_core::{format_line_numbered_with_limits, OutputLimits};
+///
+/// let limits = OutputLimits {
+///     max_line_length: 10,
+///     ..OutputLimits::default()
+/// };
+/// let formatted = format_line_numbered_with_limits("abcdefghijklmnop", 0, &limits);
+/// assert_eq!(formatted, "1\tabcdefg...");
+/// ```
+pub fn format_line_numbered_with_limits(
+    content: &str,
+    offset: usize,
+    limits: &OutputLimits,
+) -> String {
+    let lines = split_lines(content);
+    render_numbered(&lines, offset, limits.max_line_length)
+}
+
+/// Line-numbered output for a range of lines, with metadata
+///
+/// Returned by [`format_line_numbered_range`]. The metadata lets a tool tell
+/// the model exactly what it is seeing, e.g. "showing lines 101–300 of 5,000".
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormattedOutput {
+    /// The line-numbered content
+    pub content: String,
+    /// Number of lines skipped before the first shown line
+    pub offset: usize,
+    /// Number of lines shown
+    pub shown_lines: usize,
+    /// Total number of lines in the input
+    pub total_lines: usize,
+    /// Whether lines after the shown range were omitted
+    pub truncated: bool,
+}
+
+impl FormattedOutput {
+    /// 1-indexed line number of the first shown line (0 if nothing is shown)
+    pub fn first_line(&self) -> usize {
+        if self.shown_lines == 0 {
+            0
+        } else {
+            self.offset + 1
+        }
+    }
+
+    /// 1-indexed line number of the last shown line (0 if nothing is shown)
+    pub fn last_line(&self) -> usize {
+        if self.shown_lines == 0 {
+            0
+        } else {
+            self.offset + self.shown_lines
+        }
+    }
+}
+
+/// Format a range of lines from content with line numbers
+///
+/// Skips `offset` lines, then formats up to `limit` lines numbered from
+/// `offset + 1`. This replaces the split/skip/take/format sequence that read
+/// tools would otherwise reimplement.
+///
+/// # Arguments
+///
+/// * `content` - The full content
+/// * `offset` - Number of lines to skip (0-indexed)
+/// * `limit` - Maximum number of lines to show; `0` means [`MAX_READ_LINES`](crate::MAX_READ_LINES)
+///
+/// # Returns
+///
+/// A [`FormattedOutput`]. An offset past the end of the content yields an
+/// empty `content` with the correct `total_lines` rather than an error.
+///
+/// # Examples
+///
+/// ```rust
+/// use a3s_tools_core::format_line_numbered_range;
+///
+/// let content = "a\nb\nc\nd\ne";
+/// let output = format_line_numbered_range(content, 1, 2);
+/// assert_eq!(output.content, "2\tb\n3\tc");
+/// assert_eq!((output.first_line(), output.last_line()), (2, 3));
+/// assert_eq!(output.total_lines, 5);
+/// assert!(output.truncated);
+/// ```
+pub fn format_line_numbered_range(content: &str, offset: usize, limit: usize) -> FormattedOutput {
+    format_line_numbered_range_with_limits(content, offset, limit, &OutputLimits::default())
+}
+
+/// Format a range of lines with line numbers using custom limits
+///
+/// Same as [`format_line_numbered_range`], but a `limit` of `0` means
+/// `limits.max_read_lines` and long lines are cut at `limits.max_line_length`.
+pub fn format_line_numbered_range_with_limits(
+    content: &str,
+    offset: usize,
+    limit: usize,
+    limits: &OutputLimits,
+) -> FormattedOutput {
+    let limit = if limit == 0 {
+        limits.max_read_lines
+    } else {
+        limit
+    };
+
+    let lines = split_lines(content);
+    let total_lines = lines.len();
+    let start = offset.min(total_lines);
+    let end = start.saturating_add(limit).min(total_lines);
+    let shown = &lines[start..end];
+
+    FormattedOutput {
+        content: render_numbered(shown, offset, limits.max_line_length),
+        offset,
+        shown_lines: shown.len(),
+        total_lines,
+        truncated: end < total_lines,
+    }
+}
+
+/// Split content into lines without a leading BOM or trailing `\r`s
+fn split_lines(content: &str) -> Vec<&str> {
+    content
+        .strip_prefix('\u{FEFF}')
+        .unwrap_or(content)
+        .lines()
+        .map(|line| line.strip_suffix('\r').unwrap_or(line))
+        .collect()
+}
+
+/// Line ending style of a text, returned by [`detect_line_ending`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineEnding {
+    /// Unix `\n` (also reported for content without line breaks)
+    Lf,
+    /// Windows `\r\n`
+    CrLf,
+    /// Both `\n` and `\r\n` occur
+    Mixed,
+}
+
+/// Detect whether content uses LF, CRLF, or mixed line endings
+///
+/// Lets write tools preserve a file's original style after editing lines
+/// that [`format_line_numbered`] showed without their `\r`.
+///
+/// # Examples
+///
+/// ```rust
+/// use a3s_tools_core::{detect_line_ending, LineEnding};
+///
+/// assert_eq!(detect_line_ending("a\nb\n"), LineEnding::Lf);
+/// assert_eq!(detect_line_ending("a\r\nb\r\n"), LineEnding::CrLf);
+/// assert_eq!(detect_line_ending("a\r\nb\n"), LineEnding::Mixed);
+/// assert_eq!(detect_line_ending("no newline"), LineEnding::Lf);
+/// ```
+pub fn detect_line_ending(content: &str) -> LineEnding {
+    let newlines = content.matches('\n').count();
+    let crlfs = content.matches("\r\n").count();
+    match (crlfs, newlines) {
+        (0, _) => LineEnding::Lf,
+        (crlfs, newlines) if crlfs == newlines => LineEnding::CrLf,
+        _ => LineEnding::Mixed,
+    }
+}
+
+/// Heuristically detect binary content
+///
+/// Inspects the first 8,000 bytes, similar to git:
+/// - Empty content is text
+/// - Content starting with a UTF-16 byte order mark is text
+/// - Any NUL byte means binary
+/// - Otherwise, binary if more than 10% of the sample is invalid UTF-8 or
+///   control characters other than tab, newline, carriage return, form feed,
+///   backspace and escape (so ANSI-colored logs are still text)
+///
+/// # Examples
+///
+/// ```rust
+/// use a3s_tools_core::is_probably_binary;
+///
+/// assert!(!is_probably_binary(b""));
+/// assert!(!is_probably_binary(b"fn main() {}\n"));
+/// assert!(is_probably_binary(b"\x89PNG\r\n\x1a\n\x00\x00\x00\rIHDR"));
+/// ```
+pub fn is_probably_binary(bytes: &[u8]) -> bool {
+    if bytes.is_empty() || utf16_bom(bytes).is_some() {
+        return false;
+    }
+
+    let sample = &bytes[..bytes.len().min(BINARY_SAMPLE_SIZE)];
+    if sample.contains(&0) {
+        return true;
+    }
+
+    let mut suspicious = sample
+        .iter()
+        .filter(|&&b| {
+            (b < 0x20 && !matches!(b, b'\t' | b'\n' | b'\r' | 0x08 | 0x0c | 0x1b)) || b == 0x7f
+        })
+        .count();
+
+    let mut rest = sample;
+    while let Err(err) = std::str::from_utf8(rest) {
+        match err.error_len() {
+            Some(len) => {
+                suspicious += len;
+                rest = &rest[err.valid_up_to() + len..];
+            }
+            // Incomplete sequence at the end, likely cut off by the sample
+            None => break,
+        }
+    }
+
+    suspicious * 10 > sample.len()
+}
+
+/// Format raw file content with line numbers, refusing binary content
+///
+/// Decodes the bytes (UTF-8, lossily, or UTF-16 when a byte order mark is
+/// present) and formats lines `offset + 1` through `offset + limit` as
+/// [`format_line_numbered_range`] does.
+///
+/// # Arguments
+///
+/// * `bytes` - The raw file content
+/// * `offset` - Number of lines to skip (0-indexed)
+/// * `limit` - Maximum number of lines to show; `0` means [`MAX_READ_LINES`](crate::MAX_READ_LINES)
+///
+/// # Returns
+///
+/// * `Ok(String)` - The line-numbered content
+/// * `Err(ToolError::BinaryContent)` - If [`is_probably_binary`] flags the content;
+///   its message is a short description suitable to show in place of the content
+///
+/// # Examples
+///
+/// ```rust
+/// use a3s_tools_core::{format_file_content, ToolError};
+///
+/// let text = format_file_content(b"line1\nline2\n", 0, 0).unwrap();
+/// assert_eq!(text, "1\tline1\n2\tline2");
+///
+/// let err = format_file_content(b"\x7fELF\x02\x01\x01\x00", 0, 0).unwrap_err();
+/// assert!(matches!(err, ToolError::BinaryContent { size: 8 }));
+/// ```
+pub fn format_file_content(bytes: &[u8], offset: usize, limit: usize) -> Result<String, ToolError> {
+    if is_probably_binary(bytes) {
+        return Err(ToolError::BinaryContent { size: bytes.len() });
+    }
+
+    let text = decode_text(bytes);
+    Ok(format_line_numbered_range(&text, offset, limit).content)
+}
+
+/// Decode text as UTF-16 if it has a byte order mark, otherwise as lossy UTF-8
+pub(crate) fn decode_text(bytes: &[u8]) -> Cow<'_, str> {
+    match utf16_bom(bytes) {
+        Some(to_u16) => {
+            let units = bytes[2..]
+                .chunks_exact(2)
+                .map(|pair| to_u16([pair[0], pair[1]]));
+            Cow::Owned(
+                char::decode_utf16(units)
+                    .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+                    .collect(),
+            )
+        }
+        None => String::from_utf8_lossy(bytes),
+    }
+}
+
+/// Detect a UTF-16 byte order mark, returning the matching code unit decoder
+pub(crate) fn utf16_bom(bytes: &[u8]) -> Option<fn([u8; 2]) -> u16> {
+    match bytes {
+        [0xFF, 0xFE, ..] => Some(u16::from_le_bytes),
+        [0xFE, 0xFF, ..] => Some(u16::from_be_bytes),
+        _ => None,
+    }
+}
+
+/// Number `lines` starting at `offset + 1`, truncating long lines
+pub(crate) fn render_numbered(lines: &[&str], offset: usize, max_line_length: usize) -> String {
     let total_lines = offset + lines.len();
     let width = total_lines.to_string().len().max(1);
 
@@ -56,10 +362,15 @@ pub fn format_line_numbered(content: &str, offset: usize) -> String {
         .enumerate()
         .map(|(i, line)| {
             let line_num = offset + i + 1;
-            let truncated = if line.len() > MAX_LINE_LENGTH {
-                format!("{}...", &line[..MAX_LINE_LENGTH - 3])
-            } else {
-                line.to_string()
+            let truncated = match line.char_indices().nth(max_line_length) {
+                Some(_) => {
+                    let end = line
+                        .char_indices()
+                        .nth(max_line_length.saturating_sub(3))
+                        .map_or(line.len(), |(idx, _)| idx);
+                    format!("{}...", &line[..end])
+                }
+                None => line.to_string(),
             };
             format!("{:>width$}\t{}", line_num, truncated, width = width)
         })
@@ -69,17 +380,20 @@ pub fn format_line_numbered(content: &str, offset: usize) -> String {
 
 /// Truncate output if it exceeds maximum size
 ///
-/// Prevents memory exhaustion by limiting output size to [`MAX_OUTPUT_SIZE`].
+/// Prevents memory exhaustion by limiting output size to [`MAX_OUTPUT_SIZE`](crate::MAX_OUTPUT_SIZE).
 /// If the output exceeds this limit, it's truncated with a message indicating
 /// the total size and how much was shown.
 ///
+/// The cut is moved back to the nearest character boundary, so this never
+/// panics on valid UTF-8 input and never splits a multi-byte character.
+///
 /// # Arguments
 ///
 /// * `output` - The output to potentially truncate
 ///
 /// # Returns
 ///
-/// The output, truncated with a message if it exceeded [`MAX_OUTPUT_SIZE`]
+/// The output, truncated with a message if it exceeded [`MAX_OUTPUT_SIZE`](crate::MAX_OUTPUT_SIZE)
 ///
 /// # Examples
 ///
@@ -104,22 +418,507 @@ pub fn format_line_numbered(content: &str, offset: usize) -> String {
 /// assert!(result.contains("[Output truncated:"));
 /// ```
 pub fn truncate_output(output: &str) -> String {
-    if output.len() > MAX_OUTPUT_SIZE {
-        let truncated = &output[..MAX_OUTPUT_SIZE];
-        format!(
-            "{}\n\n[Output truncated: {} bytes total, showing first {} bytes]",
-            truncated,
-            output.len(),
-            MAX_OUTPUT_SIZE
-        )
-    } else {
-        output.to_string()
+    truncate_output_with_info(output).0.into_owned()
+}
+
+/// Statistics about a truncation performed by [`truncate_output_with_info`]
+///
+/// Lines are counted the way [`str::lines`] counts them. The shown counts
+/// cover the kept content only, not the appended truncation message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TruncationInfo {
+    /// Size of the input in bytes
+    pub original_bytes: usize,
+    /// Bytes of the input that were kept
+    pub shown_bytes: usize,
+    /// Number of lines in the input
+    pub original_lines: usize,
+    /// Number of lines (including a partial last line) that were kept
+    pub shown_lines: usize,
+    /// Whether anything was cut
+    pub was_truncated: bool,
+}
+
+/// Truncate output like [`truncate_output`], also returning what was cut
+///
+/// Output that fits is returned as [`Cow::Borrowed`] without copying, so this
+/// is cheap to call on every tool result. The statistics let callers build
+/// their own messages or report metrics.
+///
+/// # Examples
+///
+/// ```rust
+/// use a3s_tools_core::truncate_output_with_info;
+/// use std::borrow::Cow;
+///
+/// let (text, info) = truncate_output_with_info("one\ntwo\n");
+/// assert!(matches!(text, Cow::Borrowed("one\ntwo\n")));
+/// assert!(!info.was_truncated);
+/// assert_eq!(info.original_lines, 2);
+///
+/// let large = "line\n".repeat(50_000);
+/// let (text, info) = truncate_output_with_info(&large);
+/// assert!(info.was_truncated);
+/// assert_eq!(info.original_bytes, 250_000);
+/// assert_eq!(info.shown_bytes, 102_400);
+/// assert_eq!(info.shown_lines, 20_480);
+/// assert!(text.ends_with("[Output truncated: 244.1 KiB total, showing first 100.0 KiB]"));
+/// ```
+pub fn truncate_output_with_info(output: &str) -> (Cow<'_, str>, TruncationInfo) {
+    truncate_head(output, OutputLimits::default().max_output_size)
+}
+
+/// Truncate output if it exceeds a custom maximum size
+///
+/// Same as [`truncate_output`], but uses `limits.max_output_size` instead of
+/// [`MAX_OUTPUT_SIZE`](crate::MAX_OUTPUT_SIZE).
+///
+/// # Examples
+///
+/// ```rust
+/// use a3s_tools_core::{truncate_output_with_limits, OutputLimits};
+///
+/// let limits = OutputLimits {
+///     max_output_size: 5,
+///     ..OutputLimits::default()
+/// };
+/// let result = truncate_output_with_limits("hello world", &limits);
+/// assert!(result.starts_with("hello\n\n[Output truncated:"));
+/// ```
+pub fn truncate_output_with_limits(output: &str, limits: &OutputLimits) -> String {
+    truncate_head(output, limits.max_output_size).0.into_owned()
+}
+
+/// Keep the first `max_bytes` of `output` (on a character boundary), with statistics
+fn truncate_head(output: &str, max_bytes: usize) -> (Cow<'_, str>, TruncationInfo) {
+    let original_lines = output.lines().count();
+    if output.len() <= max_bytes {
+        let info = TruncationInfo {
+            original_bytes: output.len(),
+            shown_bytes: output.len(),
+            original_lines,
+            shown_lines: original_lines,
+            was_truncated: false,
+        };
+        return (Cow::Borrowed(output), info);
+    }
+
+    let truncated = &output[..floor_char_boundary(output, max_bytes)];
+    let info = TruncationInfo {
+        original_bytes: output.len(),
+        shown_bytes: truncated.len(),
+        original_lines,
+        shown_lines: truncated.lines().count(),
+        was_truncated: true,
+    };
+    let text = format!(
+        "{}\n\n[Output truncated: {} total, showing first {}]",
+        truncated,
+        format_bytes(output.len() as u64),
+        format_bytes(truncated.len() as u64)
+    );
+    (Cow::Owned(text), info)
+}
+
+/// Which part of an oversized output to keep when truncating
+///
+/// Used by [`truncate_output_with_mode`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TruncateMode {
+    /// Keep the beginning of the output (same as [`truncate_output`])
+    #[default]
+    Head,
+    /// Keep the end of the output, where compiler errors and test failures usually are
+    Tail,
+    /// Keep the first and last halves, with an elision marker in the middle
+    HeadAndTail,
+}
+
+/// Truncate output keeping its end if it exceeds maximum size
+///
+/// Keeps the last [`MAX_OUTPUT_SIZE`](crate::MAX_OUTPUT_SIZE) bytes (or fewer)
+/// and prepends a header indicating how much was shown. Useful for command
+/// output, where the interesting part is usually at the end.
+///
+/// The cut always lands on a character boundary, and is moved forward to the
+/// start of the next line when there is one.
+///
+/// # Examples
+///
+/// ```rust
+/// use a3s_tools_core::truncate_output_tail;
+///
+/// assert_eq!(truncate_output_tail("hello world"), "hello world");
+///
+/// let large = "x".repeat(200_000) + "\nerror: build failed";
+/// let result = truncate_output_tail(&large);
+/// assert!(result.starts_with("[Output truncated: showing last"));
+/// assert!(result.ends_with("error: build failed"));
+/// ```
+pub fn truncate_output_tail(output: &str) -> String {
+    truncate_output_with_mode(output, TruncateMode::Tail, &OutputLimits::default())
+}
+
+/// Truncate output using the given [`TruncateMode`] and limits
+///
+/// Output within `limits.max_output_size` is returned unchanged in every mode.
+///
+/// - [`TruncateMode::Head`] - Same as [`truncate_output_with_limits`]
+/// - [`TruncateMode::Tail`] - Keeps the end, with a header:
+///   `[Output truncated: showing last X of Y bytes]`
+/// - [`TruncateMode::HeadAndTail`] - Keeps roughly half the budget from each
+///   end, with a marker in the middle:
+///   `[Output truncated: N bytes omitted, showing first X and last Y of Z bytes]`
+///
+/// Cuts land on character boundaries, and on line boundaries where possible.
+///
+/// # Examples
+///
+/// ```rust
+/// use a3s_tools_core::{truncate_output_with_mode, OutputLimits, TruncateMode};
+///
+/// let limits = OutputLimits {
+///     max_output_size: 12,
+///     ..OutputLimits::default()
+/// };
+/// let output = "first\nsecond\nthird\nfourth\nlast";
+/// let result = truncate_output_with_mode(output, TruncateMode::HeadAndTail, &limits);
+/// assert!(result.starts_with("first\n\n[Output truncated:"));
+/// assert!(result.ends_with("]\n\nlast"));
+/// ```
+pub fn truncate_output_with_mode(
+    output: &str,
+    mode: TruncateMode,
+    limits: &OutputLimits,
+) -> String {
+    let max_output_size = limits.max_output_size;
+    if output.len() <= max_output_size {
+        return output.to_string();
+    }
+
+    match mode {
+        TruncateMode::Head => truncate_output_with_limits(output, limits),
+        TruncateMode::Tail => {
+            let tail = tail_on_line_boundary(output, max_output_size);
+            format!(
+                "[Output truncated: showing last {} of {} bytes]\n\n{}",
+                format_count(tail.len()),
+                format_count(output.len()),
+                tail
+            )
+        }
+        TruncateMode::HeadAndTail => {
+            let head = head_on_line_boundary(output, max_output_size / 2);
+            let tail = tail_on_line_boundary(output, max_output_size - head.len());
+            format!(
+                "{}\n\n[Output truncated: {} bytes omitted, showing first {} and last {} of {} bytes]\n\n{}",
+                head,
+                format_count(output.len() - head.len() - tail.len()),
+                format_count(head.len()),
+                format_count(tail.len()),
+                format_count(output.len()),
+                tail
+            )
+        }
+    }
+}
+
+/// Take at most `max_bytes` from the start, ending at a line boundary if any
+///
+/// A cut that already lands just before a newline keeps the last line whole.
+fn head_on_line_boundary(output: &str, max_bytes: usize) -> &str {
+    let end = floor_char_boundary(output, max_bytes);
+    let head = &output[..end];
+    if end == output.len() || output.as_bytes()[end] == b'\n' {
+        return head;
+    }
+    match head.rfind('\n') {
+        Some(idx) => &head[..idx],
+        None => head,
+    }
+}
+
+/// Take at most `max_bytes` from the end, starting at a line boundary if any
+///
+/// A cut that already lands just after a newline keeps the first line whole.
+fn tail_on_line_boundary(output: &str, max_bytes: usize) -> &str {
+    let start = ceil_char_boundary(output, output.len().saturating_sub(max_bytes));
+    let tail = &output[start..];
+    if start == 0 || output.as_bytes()[start - 1] == b'\n' {
+        return tail;
+    }
+    match tail.find('\n') {
+        Some(idx) if idx + 1 < tail.len() => &tail[idx + 1..],
+        _ => tail,
+    }
+}
+
+/// Truncate output on a line boundary if it exceeds `max_bytes`
+///
+/// Like [`truncate_output`], but cuts at the last complete line that fits
+/// under `max_bytes`, so line-oriented output (JSON lines, diffs) isn't left
+/// with a half-written final line. The truncation message reports how many
+/// lines were shown out of the total.
+///
+/// If the first line alone is larger than `max_bytes`, it is cut on a
+/// character boundary instead. Output that fits within `max_bytes` is
+/// returned unchanged.
+///
+/// # Arguments
+///
+/// * `output` - The output to potentially truncate
+/// * `max_bytes` - Maximum number of bytes of output to keep
+///
+/// # Examples
+///
+/// ```rust
+/// use a3s_tools_core::truncate_output_lines;
+///
+/// let output = "line1\nline2\nline3\n";
+/// let result = truncate_output_lines(output, 14);
+/// assert!(result.starts_with("line1\nline2\n\n[Output truncated: 2 of 3 lines shown"));
+///
+/// assert_eq!(truncate_output_lines(output, output.len()), output);
+/// ```
+pub fn truncate_output_lines(output: &str, max_bytes: usize) -> String {
+    if output.len() <= max_bytes {
+        return output.to_string();
+    }
+
+    let head = &output[..floor_char_boundary(output, max_bytes)];
+    let truncated = match head.rfind('\n') {
+        Some(idx) => &head[..idx],
+        // A single line larger than the limit: cut it on a char boundary
+        None => head,
+    };
+
+    format!(
+        "{}\n\n[Output truncated: {} of {} lines shown, {} limit]",
+        truncated,
+        format_count(truncated.lines().count()),
+        format_count(output.lines().count()),
+        format_bytes(max_bytes as u64)
+    )
+}
+
+/// Line counts for a diff, returned by [`generate_diff_stats`]
+#[cfg(feature = "diff")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DiffStats {
+    /// Lines present only in the new text
+    pub added: usize,
+    /// Lines present only in the old text
+    pub removed: usize,
+}
+
+/// Generate a unified diff between two texts
+///
+/// Requires the `diff` feature. Produces standard unified-diff hunks
+/// (`@@ -a,b +c,d @@` headers with 1-based line numbers) without `---`/`+++`
+/// file headers, so callers can add their own. Identical inputs produce an
+/// empty string. A missing trailing newline is marked with
+/// `\ No newline at end of file`, and CRLF line endings are kept as-is, so
+/// changing only line endings shows up as changed lines.
+///
+/// Output is capped at [`MAX_OUTPUT_SIZE`](crate::MAX_OUTPUT_SIZE) like
+/// [`truncate_output`], but cut between hunks rather than in the middle of one.
+/// Only a first hunk that alone exceeds the cap is cut mid-hunk. Either way a
+/// `[Diff truncated: ...]` note reports how many hunks were dropped.
+///
+/// # Arguments
+///
+/// * `old` - The original text
+/// * `new` - The modified text
+/// * `context_lines` - Unchanged lines to show around each change (3 is typical)
+///
+/// # Examples
+///
+/// ```rust
+/// use a3s_tools_core::generate_diff;
+///
+/// let diff = generate_diff("a\nb\nc\n", "a\nB\nc\n", 1);
+/// assert_eq!(diff, "@@ -1,3 +1,3 @@\n a\n-b\n+B\n c\n");
+///
+/// assert_eq!(generate_diff("same\n", "same\n", 3), "");
+/// ```
+#[cfg(feature = "diff")]
+pub fn generate_diff(old: &str, new: &str, context_lines: usize) -> String {
+    generate_diff_with_limits(old, new, context_lines, &OutputLimits::default())
+}
+
+/// Generate a unified diff with a custom size budget
+///
+/// Same as [`generate_diff`], but caps the output at `limits.max_output_size`.
+#[cfg(feature = "diff")]
+pub fn generate_diff_with_limits(
+    old: &str,
+    new: &str,
+    context_lines: usize,
+    limits: &OutputLimits,
+) -> String {
+    if old == new {
+        return String::new();
+    }
+
+    let diff = similar::TextDiff::from_lines(old, new);
+    let mut unified = diff.unified_diff();
+    unified.context_radius(context_lines);
+    let hunks: Vec<String> = unified.iter_hunks().map(|hunk| hunk.to_string()).collect();
+
+    let max_bytes = limits.max_output_size;
+    let mut output = String::new();
+    let mut shown = 0;
+    for hunk in &hunks {
+        if output.len() + hunk.len() > max_bytes {
+            break;
+        }
+        output.push_str(hunk);
+        shown += 1;
+    }
+
+    if shown == hunks.len() {
+        return output;
+    }
+
+    let total: usize = hunks.iter().map(String::len).sum();
+    if shown == 0 {
+        // A single hunk over budget has to be cut mid-hunk
+        let head = head_on_line_boundary(&hunks[0], max_bytes);
+        return format!(
+            "{}\n\n[Diff truncated: hunk 1 partially shown, {} more hunks omitted, {} omitted]",
+            head,
+            format_count(hunks.len() - 1),
+            format_bytes((total - head.len()) as u64)
+        );
+    }
+
+    let omitted = total - output.len();
+    format!(
+        "{}\n[Diff truncated: {} of {} hunks shown, {} omitted]",
+        output,
+        format_count(shown),
+        format_count(hunks.len()),
+        format_bytes(omitted as u64)
+    )
+}
+
+/// Count added and removed lines between two texts
+///
+/// Requires the `diff` feature. Uses the same line diff as [`generate_diff`],
+/// so a changed line counts as one removal plus one addition.
+///
+/// # Examples
+///
+/// ```rust
+/// use a3s_tools_core::{generate_diff_stats, DiffStats};
+///
+/// let stats = generate_diff_stats("a\nb\nc\n", "a\nB\nc\nd\n");
+/// assert_eq!(stats, DiffStats { added: 2, removed: 1 });
+/// ```
+#[cfg(feature = "diff")]
+pub fn generate_diff_stats(old: &str, new: &str) -> DiffStats {
+    let mut stats = DiffStats::default();
+    for change in similar::TextDiff::from_lines(old, new).iter_all_changes() {
+        match change.tag() {
+            similar::ChangeTag::Insert => stats.added += 1,
+            similar::ChangeTag::Delete => stats.removed += 1,
+            similar::ChangeTag::Equal => {}
+        }
+    }
+    stats
+}
+
+/// Format a count with comma thousands separators
+///
+/// # Examples
+///
+/// ```rust
+/// use a3s_tools_core::format_count;
+///
+/// assert_eq!(format_count(999), "999");
+/// assert_eq!(format_count(9_841), "9,841");
+/// assert_eq!(format_count(1_234_567), "1,234,567");
+/// ```
+pub fn format_count(n: usize) -> String {
+    let digits = n.to_string();
+    let mut grouped = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            grouped.push(',');
+        }
+        grouped.push(c);
+    }
+    grouped
+}
+
+/// Format a byte count for humans
+///
+/// The output is deterministic, since messages built with it are matched by
+/// downstream tests:
+/// - Below 1024: the exact count, `"0 bytes"`, `"1 byte"`, `"1023 bytes"`
+/// - Otherwise: binary units `KiB`, `MiB`, `GiB`, `TiB`, `PiB`, `EiB` with
+///   exactly one decimal place, rounded half up (`"1.0 KiB"`, `"100.0 KiB"`, `"2.3 MiB"`)
+/// - A value that rounds up to 1024.0 of a unit is shown as 1.0 of the next
+///   one (`1_048_575` is `"1.0 MiB"`, not `"1024.0 KiB"`)
+///
+/// # Examples
+///
+/// ```rust
+/// use a3s_tools_core::format_bytes;
+///
+/// assert_eq!(format_bytes(512), "512 bytes");
+/// assert_eq!(format_bytes(102_400), "100.0 KiB");
+/// assert_eq!(format_bytes(2_411_725), "2.3 MiB");
+/// ```
+pub fn format_bytes(n: u64) -> String {
+    const UNITS: [&str; 6] = ["KiB", "MiB", "GiB", "TiB", "PiB", "EiB"];
+    match n {
+        1 => return "1 byte".to_string(),
+        0..=1023 => return format!("{} bytes", n),
+        _ => {}
+    }
+
+    let mut unit = 0;
+    let mut unit_size: u128 = 1024;
+    // Rounded to tenths of the current unit, half up, in integer arithmetic
+    let tenths = |unit_size: u128| (u128::from(n) * 10 + unit_size / 2) / unit_size;
+    while unit + 1 < UNITS.len() && tenths(unit_size) >= 10 * 1024 {
+        unit += 1;
+        unit_size *= 1024;
+    }
+
+    let tenths = tenths(unit_size);
+    format!("{}.{} {}", tenths / 10, tenths % 10, UNITS[unit])
+}
+
+/// Find the largest char boundary in `s` that is not greater than `index`
+fn floor_char_boundary(s: &str, index: usize) -> usize {
+    if index >= s.len() {
+        return s.len();
+    }
+    let mut idx = index;
+    while !s.is_char_boundary(idx) {
+        idx -= 1;
+    }
+    idx
+}
+
+/// Find the smallest char boundary in `s` that is not less than `index`
+fn ceil_char_boundary(s: &str, index: usize) -> usize {
+    if index >= s.len() {
+        return s.len();
+    }
+    let mut idx = index;
+    while !s.is_char_boundary(idx) {
+        idx += 1;
     }
+    idx
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::constants::{MAX_LINE_LENGTH, MAX_OUTPUT_SIZE, MAX_READ_LINES};
 
     #[test]
     fn test_format_line_numbered() {
@@ -150,6 +949,203 @@ mod tests {
         assert!(result.contains("..."));
     }
 
+    #[test]
+    fn test_format_line_numbered_multibyte_exactly_at_limit() {
+        let line = "你".repeat(MAX_LINE_LENGTH);
+        let result = format_line_numbered(&line, 0);
+
+        assert_eq!(result, format!("1\t{}", line));
+    }
+
+    #[test]
+    fn test_format_line_numbered_multibyte_just_under_limit() {
+        let line = "你".repeat(MAX_LINE_LENGTH - 1);
+        let result = format_line_numbered(&line, 0);
+
+        assert_eq!(result, format!("1\t{}", line));
+    }
+
+    #[test]
+    fn test_format_line_numbered_multibyte_just_over_limit() {
+        let line = "你".repeat(MAX_LINE_LENGTH + 1);
+        let result = format_line_numbered(&line, 0);
+
+        let expected = format!("1\t{}...", "你".repeat(MAX_LINE_LENGTH - 3));
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_format_line_numbered_emoji_long_line() {
+        let line = "😀".repeat(3000);
+        let result = format_line_numbered(&line, 0);
+
+        assert!(result.ends_with("..."));
+        assert_eq!(result.chars().count(), "1\t".len() + MAX_LINE_LENGTH);
+    }
+
+    #[test]
+    fn test_format_line_numbered_with_limits() {
+        let limits = OutputLimits {
+            max_line_length: 5,
+            ..OutputLimits::default()
+        };
+        let result = format_line_numbered_with_limits("12345\n123456", 0, &limits);
+
+        assert_eq!(result, "1\t12345\n2\t12...");
+    }
+
+    #[test]
+    fn test_truncate_output_with_limits() {
+        let limits = OutputLimits {
+            max_output_size: 200 * 1024,
+            ..OutputLimits::default()
+        };
+        let output = "x".repeat(150 * 1024);
+        assert_eq!(truncate_output_with_limits(&output, &limits), output);
+
+        let output = "x".repeat(250 * 1024);
+        let result = truncate_output_with_limits(&output, &limits);
+        assert!(result.ends_with("[Output truncated: 250.0 KiB total, showing first 200.0 KiB]"));
+    }
+
+    #[test]
+    fn test_format_line_numbered_range_middle() {
+        let content = (1..=10)
+            .map(|i| format!("line{i}"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let output = format_line_numbered_range(&content, 3, 4);
+
+        assert_eq!(output.content, "4\tline4\n5\tline5\n6\tline6\n7\tline7");
+        assert_eq!(output.offset, 3);
+        assert_eq!(output.shown_lines, 4);
+        assert_eq!(output.total_lines, 10);
+        assert!(output.truncated);
+        assert_eq!((output.first_line(), output.last_line()), (4, 7));
+    }
+
+    #[test]
+    fn test_format_line_numbered_range_to_end() {
+        let output = format_line_numbered_range("a\nb\nc", 1, 10);
+
+        assert_eq!(output.content, "2\tb\n3\tc");
+        assert_eq!(output.shown_lines, 2);
+        assert!(!output.truncated);
+    }
+
+    #[test]
+    fn test_format_line_numbered_range_offset_past_eof() {
+        let output = format_line_numbered_range("a\nb\nc", 50, 10);
+
+        assert_eq!(output.content, "");
+        assert_eq!(output.shown_lines, 0);
+        assert_eq!(output.total_lines, 3);
+        assert!(!output.truncated);
+        assert_eq!((output.first_line(), output.last_line()), (0, 0));
+    }
+
+    #[test]
+    fn test_format_line_numbered_range_zero_limit_uses_default() {
+        let content = "x\n".repeat(MAX_READ_LINES + 5);
+        let output = format_line_numbered_range(&content, 0, 0);
+
+        assert_eq!(output.shown_lines, MAX_READ_LINES);
+        assert_eq!(output.total_lines, MAX_READ_LINES + 5);
+        assert!(output.truncated);
+    }
+
+    #[test]
+    fn test_format_line_numbered_range_matches_wrapper() {
+        let content = "a\nb\nc";
+        let output = format_line_numbered_range(content, 0, 0);
+        assert_eq!(output.content, format_line_numbered(content, 0));
+    }
+
+    fn utf16le(text: &str) -> Vec<u8> {
+        let mut bytes = vec![0xFF, 0xFE];
+        bytes.extend(text.encode_utf16().flat_map(u16::to_le_bytes));
+        bytes
+    }
+
+    #[test]
+    fn test_is_probably_binary_empty() {
+        assert!(!is_probably_binary(b""));
+        assert_eq!(format_file_content(b"", 0, 0).unwrap(), "");
+    }
+
+    #[test]
+    fn test_is_probably_binary_text_with_control_chars() {
+        let log = b"\x1b[31merror\x1b[0m: build failed\r\n\tat line 3\x0c\nprogress\x08\x08 done\n";
+        assert!(!is_probably_binary(log));
+
+        // An occasional stray control character doesn't make a file binary
+        let mut text = "ordinary text line\n".repeat(20).into_bytes();
+        text[5] = 0x01;
+        assert!(!is_probably_binary(&text));
+    }
+
+    #[test]
+    fn test_is_probably_binary_binary_data() {
+        let png = b"\x89PNG\r\n\x1a\n\x00\x00\x00\rIHDR\x00\x00\x01\x00";
+        assert!(is_probably_binary(png));
+
+        // No NUL bytes, but mostly invalid UTF-8 and control characters
+        let noise: Vec<u8> = (0..1000u32).map(|i| (i * 37 % 251 + 1) as u8).collect();
+        assert!(is_probably_binary(&noise));
+    }
+
+    #[test]
+    fn test_is_probably_binary_latin1_text() {
+        // A few invalid UTF-8 bytes in otherwise plain text
+        let mut text = b"caf\xe9 au lait\n".to_vec();
+        text.extend("plain ascii text\n".repeat(10).as_bytes());
+        assert!(!is_probably_binary(&text));
+    }
+
+    #[test]
+    fn test_is_probably_binary_utf16() {
+        let bytes = utf16le("hello\nworld\n");
+        assert!(!is_probably_binary(&bytes));
+        assert_eq!(
+            format_file_content(&bytes, 0, 0).unwrap(),
+            "1\thello\n2\tworld"
+        );
+
+        let mut be = vec![0xFE, 0xFF];
+        be.extend("hi\n".encode_utf16().flat_map(u16::to_be_bytes));
+        assert_eq!(format_file_content(&be, 0, 0).unwrap(), "1\thi");
+    }
+
+    #[test]
+    fn test_format_file_content_binary_error() {
+        let bytes = vec![0u8; 48_213];
+        let err = format_file_content(&bytes, 0, 0).unwrap_err();
+        assert!(matches!(err, ToolError::BinaryContent { size: 48_213 }));
+    }
+
+    #[test]
+    fn test_format_file_content_range() {
+        let text = format_file_content(b"a\nb\nc\nd", 1, 2).unwrap();
+        assert_eq!(text, "2\tb\n3\tc");
+    }
+
+    #[test]
+    fn test_format_bytes_boundaries() {
+        assert_eq!(format_bytes(0), "0 bytes");
+        assert_eq!(format_bytes(1), "1 byte");
+        assert_eq!(format_bytes(1023), "1023 bytes");
+        assert_eq!(format_bytes(1024), "1.0 KiB");
+        assert_eq!(format_bytes(1025), "1.0 KiB");
+        // 1.05 KiB rounds half up
+        assert_eq!(format_bytes(1075), "1.0 KiB");
+        assert_eq!(format_bytes(1076), "1.1 KiB");
+        assert_eq!(format_bytes(MAX_OUTPUT_SIZE as u64), "100.0 KiB");
+        assert_eq!(format_bytes(1024 * 1024 - 1), "1.0 MiB");
+        assert_eq!(format_bytes(1024 * 1024), "1.0 MiB");
+        assert_eq!(format_bytes(3_113_851_290), "2.9 GiB");
+        assert_eq!(format_bytes(u64::MAX), "16.0 EiB");
+    }
+
     #[test]
     fn test_truncate_output_small() {
         let small = "hello world";
@@ -165,4 +1161,356 @@ mod tests {
         assert!(result.len() < large.len());
         assert!(result.contains("[Output truncated:"));
     }
+
+    #[test]
+    fn test_truncate_output_with_info_borrows_small_input() {
+        let small = "hello\nworld";
+        let (result, info) = truncate_output_with_info(small);
+
+        // Borrowed and pointing at the input: nothing was copied
+        match result {
+            Cow::Borrowed(text) => assert!(std::ptr::eq(text, small)),
+            Cow::Owned(_) => panic!("small output should not be copied"),
+        }
+        assert_eq!(
+            info,
+            TruncationInfo {
+                original_bytes: 11,
+                shown_bytes: 11,
+                original_lines: 2,
+                shown_lines: 2,
+                was_truncated: false,
+            }
+        );
+
+        let exact = "x".repeat(MAX_OUTPUT_SIZE);
+        assert!(matches!(
+            truncate_output_with_info(&exact).0,
+            Cow::Borrowed(_)
+        ));
+        assert!(matches!(truncate_output_with_info("").0, Cow::Borrowed("")));
+    }
+
+    #[test]
+    fn test_truncate_output_with_info_large() {
+        // 9-byte lines; the cut lands mid-line, which counts as a shown line
+        let large = "12345678\n".repeat(MAX_OUTPUT_SIZE / 9 + 100);
+        let (result, info) = truncate_output_with_info(&large);
+
+        assert!(matches!(result, Cow::Owned(_)));
+        assert!(info.was_truncated);
+        assert_eq!(info.original_bytes, large.len());
+        assert_eq!(info.shown_bytes, MAX_OUTPUT_SIZE);
+        assert_eq!(info.original_lines, MAX_OUTPUT_SIZE / 9 + 100);
+        assert_eq!(info.shown_lines, MAX_OUTPUT_SIZE / 9 + 1);
+        assert_eq!(result, truncate_output(&large));
+    }
+
+    #[test]
+    fn test_truncate_output_multibyte_boundary() {
+        // 3-byte chars; MAX_OUTPUT_SIZE is not a multiple of 3, so a naive
+        // byte slice would land in the middle of a character
+        let large = "你".repeat(MAX_OUTPUT_SIZE / 3 + 10);
+        let result = truncate_output(&large);
+
+        let shown = (MAX_OUTPUT_SIZE / 3) * 3;
+        assert!(result.starts_with(&large[..shown]));
+        assert!(result[shown..].starts_with("\n\n[Output truncated:"));
+    }
+
+    #[test]
+    fn test_truncate_output_lines_fits_exactly() {
+        let output = "line1\nline2\nline3";
+        let result = truncate_output_lines(output, output.len());
+        assert_eq!(result, output);
+    }
+
+    #[test]
+    fn test_truncate_output_lines_cuts_on_newline() {
+        let output = "{\"a\":1}\n{\"b\":2}\n{\"c\":3}\n";
+        // Limit lands in the middle of the second line
+        let result = truncate_output_lines(output, 12);
+
+        assert_eq!(
+            result,
+            "{\"a\":1}\n\n[Output truncated: 1 of 3 lines shown, 12 bytes limit]"
+        );
+    }
+
+    #[test]
+    fn test_truncate_output_lines_message_format() {
+        let output = "x".repeat(99).to_string() + "\n";
+        let output = output.repeat(9_841);
+        let result = truncate_output_lines(&output, MAX_OUTPUT_SIZE);
+
+        // 100 bytes per line, so 1,024 complete lines fit in 100KB
+        assert!(result.ends_with("[Output truncated: 1,024 of 9,841 lines shown, 100.0 KiB limit]"));
+        assert!(result.starts_with(&output[..1_024 * 100 - 1]));
+    }
+
+    #[test]
+    fn test_truncate_output_lines_single_long_line() {
+        let output = "你".repeat(100);
+        let result = truncate_output_lines(&output, 10);
+
+        // 10 bytes holds 3 whole 3-byte chars
+        assert_eq!(
+            result,
+            "你你你\n\n[Output truncated: 1 of 1 lines shown, 10 bytes limit]"
+        );
+    }
+
+    #[test]
+    fn test_format_count() {
+        assert_eq!(format_count(0), "0");
+        assert_eq!(format_count(999), "999");
+        assert_eq!(format_count(1_000), "1,000");
+        assert_eq!(format_count(1_234_567), "1,234,567");
+    }
+
+    #[test]
+    fn test_truncate_modes_small_output_untouched() {
+        let small = "hello\nworld";
+        for mode in [
+            TruncateMode::Head,
+            TruncateMode::Tail,
+            TruncateMode::HeadAndTail,
+        ] {
+            let result = truncate_output_with_mode(small, mode, &OutputLimits::default());
+            assert_eq!(result, small);
+        }
+        assert_eq!(truncate_output_tail(small), small);
+    }
+
+    #[test]
+    fn test_truncate_output_tail_marker_and_line_boundary() {
+        let limits = OutputLimits {
+            max_output_size: 10,
+            ..OutputLimits::default()
+        };
+        let output = "aaaa\nbbbb\ncccc\ndd";
+        let result = truncate_output_with_mode(output, TruncateMode::Tail, &limits);
+
+        // Last 10 bytes are "bb\ncccc\ndd"; the partial "bb" line is dropped
+        assert_eq!(
+            result,
+            "[Output truncated: showing last 7 of 17 bytes]\n\ncccc\ndd"
+        );
+    }
+
+    #[test]
+    fn test_truncate_output_cut_on_line_start() {
+        let output = "aaaa\nbbbb\ncccc\ndd";
+
+        // Last 12 bytes start exactly at "bbbb", so that line is kept
+        let limits = OutputLimits {
+            max_output_size: 12,
+            ..OutputLimits::default()
+        };
+        let result = truncate_output_with_mode(output, TruncateMode::Tail, &limits);
+        assert_eq!(
+            result,
+            "[Output truncated: showing last 12 of 17 bytes]\n\nbbbb\ncccc\ndd"
+        );
+
+        // First 4 bytes end exactly before a newline, so "aaaa" is kept
+        assert_eq!(head_on_line_boundary(output, 4), "aaaa");
+        assert_eq!(head_on_line_boundary(output, 7), "aaaa");
+        assert_eq!(tail_on_line_boundary(output, 8), "cccc\ndd");
+    }
+
+    #[test]
+    fn test_truncate_output_tail_char_boundary() {
+        let output = "你".repeat(MAX_OUTPUT_SIZE / 3 + 10);
+        let result = truncate_output_tail(&output);
+
+        let shown = (MAX_OUTPUT_SIZE / 3) * 3;
+        assert!(result.ends_with(&output[output.len() - shown..]));
+        assert!(result.contains(&format!("showing last {} of", format_count(shown))));
+    }
+
+    #[test]
+    fn test_truncate_output_head_and_tail_marker_in_middle() {
+        let limits = OutputLimits {
+            max_output_size: 20,
+            ..OutputLimits::default()
+        };
+        let output = "head1\nhead2\nmiddle\nmiddle\nmiddle\ntail1\ntail2";
+        let result = truncate_output_with_mode(output, TruncateMode::HeadAndTail, &limits);
+
+        assert_eq!(
+            result,
+            "head1\n\n[Output truncated: 28 bytes omitted, showing first 5 and last 11 of 44 bytes]\n\ntail1\ntail2"
+        );
+    }
+
+    #[test]
+    fn test_truncate_output_with_mode_head() {
+        let limits = OutputLimits {
+            max_output_size: 5,
+            ..OutputLimits::default()
+        };
+        let result = truncate_output_with_mode("hello world", TruncateMode::Head, &limits);
+        assert_eq!(result, truncate_output_with_limits("hello world", &limits));
+    }
+
+    #[test]
+    fn test_format_line_numbered_crlf() {
+        let content = "first\r\nsecond\r\nthird\r\n";
+        assert_eq!(
+            format_line_numbered(content, 0),
+            "1\tfirst\n2\tsecond\n3\tthird"
+        );
+        // A lone trailing `\r` (no `\n`) is stripped too
+        assert_eq!(format_line_numbered("a\r\nb\r", 0), "1\ta\n2\tb");
+        assert_eq!(detect_line_ending(content), LineEnding::CrLf);
+    }
+
+    #[test]
+    fn test_format_line_numbered_bom() {
+        assert_eq!(format_line_numbered("\u{FEFF}", 0), "");
+        assert_eq!(
+            format_line_numbered("\u{FEFF}first\r\nsecond", 0),
+            "1\tfirst\n2\tsecond"
+        );
+        // Only a leading BOM is removed
+        assert_eq!(
+            format_line_numbered("a\n\u{FEFF}b", 0),
+            "1\ta\n2\t\u{FEFF}b"
+        );
+
+        let output = format_line_numbered_range("\u{FEFF}one\r\ntwo\r\n", 0, 1);
+        assert_eq!(output.content, "1\tone");
+        assert_eq!(output.total_lines, 2);
+
+        let text = format_file_content(b"\xEF\xBB\xBFhello\r\n", 0, 0).unwrap();
+        assert_eq!(text, "1\thello");
+    }
+
+    #[test]
+    fn test_detect_line_ending_mixed() {
+        let content = "unix\nwindows\r\nunix again\n";
+        assert_eq!(detect_line_ending(content), LineEnding::Mixed);
+        assert_eq!(
+            format_line_numbered(content, 0),
+            "1\tunix\n2\twindows\n3\tunix again"
+        );
+
+        assert_eq!(detect_line_ending(""), LineEnding::Lf);
+        assert_eq!(detect_line_ending("old mac\r"), LineEnding::Lf);
+    }
+
+    #[cfg(feature = "diff")]
+    #[test]
+    fn test_generate_diff_identical() {
+        assert_eq!(generate_diff("", "", 3), "");
+        assert_eq!(generate_diff("a\nb\n", "a\nb\n", 3), "");
+        assert_eq!(generate_diff_stats("a\n", "a\n"), DiffStats::default());
+    }
+
+    #[cfg(feature = "diff")]
+    #[test]
+    fn test_generate_diff_hunks_and_context() {
+        let old: String = (1..=20).map(|i| format!("line {}\n", i)).collect();
+        let new = old
+            .replace("line 2\n", "line two\n")
+            .replace("line 18\n", "");
+
+        let diff = generate_diff(&old, &new, 1);
+        assert_eq!(
+            diff,
+            "@@ -1,3 +1,3 @@\n line 1\n-line 2\n+line two\n line 3\n\
+             @@ -17,3 +17,2 @@\n line 17\n-line 18\n line 19\n"
+        );
+        assert_eq!(
+            generate_diff_stats(&old, &new),
+            DiffStats {
+                added: 1,
+                removed: 2
+            }
+        );
+    }
+
+    #[cfg(feature = "diff")]
+    #[test]
+    fn test_generate_diff_trailing_newline() {
+        let diff = generate_diff("a\nb", "a\nb\n", 3);
+        assert_eq!(
+            diff,
+            "@@ -1,2 +1,2 @@\n a\n-b\n\\ No newline at end of file\n+b\n"
+        );
+    }
+
+    #[cfg(feature = "diff")]
+    #[test]
+    fn test_generate_diff_crlf() {
+        let diff = generate_diff("a\r\nb\r\nc\r\n", "a\r\nB\r\nc\r\n", 1);
+        assert_eq!(diff, "@@ -1,3 +1,3 @@\n a\r\n-b\r\n+B\r\n c\r\n");
+
+        // Converting line endings changes every line
+        let stats = generate_diff_stats("a\r\nb\r\n", "a\nb\n");
+        assert_eq!(
+            stats,
+            DiffStats {
+                added: 2,
+                removed: 2
+            }
+        );
+    }
+
+    #[cfg(feature = "diff")]
+    #[test]
+    fn test_generate_diff_truncates_whole_hunks() {
+        // Changes every 10 lines, so each gets its own hunk with 1 line of context
+        let old: String = (0..100).map(|i| format!("line {}\n", i)).collect();
+        let new: String = (0..100)
+            .map(|i| {
+                if i % 10 == 5 {
+                    format!("changed {}\n", i)
+                } else {
+                    format!("line {}\n", i)
+                }
+            })
+            .collect();
+        let full = generate_diff(&old, &new, 1);
+        let hunk_starts: Vec<usize> = full.match_indices("@@ -").map(|(i, _)| i).collect();
+        assert_eq!(hunk_starts.len(), 10);
+
+        // Room for two hunks and part of the third
+        let limits = OutputLimits {
+            max_output_size: hunk_starts[2] + 5,
+            ..OutputLimits::default()
+        };
+        let diff = generate_diff_with_limits(&old, &new, 1, &limits);
+        let (hunks, marker) = diff.split_at(diff.find("\n[Diff truncated:").unwrap());
+        assert!(full.starts_with(hunks));
+        assert_eq!(hunks.matches("@@ -").count(), 2);
+        assert!(marker.starts_with("\n[Diff truncated: 2 of 10 hunks shown,"));
+
+        // A single hunk over the budget is cut, with one note covering the rest
+        let limits = OutputLimits {
+            max_output_size: 10,
+            ..OutputLimits::default()
+        };
+        let diff = generate_diff_with_limits(&old, &new, 1, &limits);
+        assert_eq!(
+            diff,
+            format!(
+                "@@ -5,3 +5\n\n[Diff truncated: hunk 1 partially shown, 9 more hunks omitted, {} omitted]",
+                format_bytes((full.len() - 10) as u64)
+            )
+        );
+
+        // The cut lands on a line boundary when one fits
+        let first_line_len = full.find('\n').unwrap() + 1;
+        let limits = OutputLimits {
+            max_output_size: first_line_len + 3,
+            ..OutputLimits::default()
+        };
+        let diff = generate_diff_with_limits(&old, &new, 1, &limits);
+        assert!(diff.starts_with(&format!(
+            "{}\n\n[Diff truncated: hunk 1",
+            &full[..first_line_len - 1]
+        )));
+    }
 }
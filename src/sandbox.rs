@@ -6,6 +6,10 @@
 //! - [`resolve_path`] - For reading existing files (requires file to exist)
 //! - [`resolve_path_for_write`] - For writing files (allows non-existent files)
 //!
+//! When resolving many paths against the same workspace, construct a [`Sandbox`]
+//! once and use [`Sandbox::resolve`] / [`Sandbox::resolve_for_write`] instead.
+//! The sandbox canonicalizes the workspace root up front rather than on every call.
+//!
 //! # Security
 //!
 //! Both functions enforce workspace boundaries by:
@@ -31,8 +35,725 @@
 //! # }
 //! ```
 
+use crate::constants::MAX_SYMLINK_DEPTH;
 use crate::ToolError;
-use std::path::{Path, PathBuf};
+use std::borrow::Cow;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+/// A workspace sandbox with a pre-canonicalized root
+///
+/// Canonicalizing the workspace is a syscall; the free functions
+/// [`resolve_path`] and [`resolve_path_for_write`] do it on every call.
+/// A `Sandbox` does it once at construction, which matters for tools that
+/// resolve thousands of paths (e.g. a grep tool walking a repository).
+///
+/// # Examples
+///
+/// ```rust
+/// use a3s_tools_core::Sandbox;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let dir = tempfile::tempdir()?;
+/// let sandbox = Sandbox::new(dir.path())?;
+///
+/// std::fs::write(dir.path().join("main.rs"), "fn main() {}")?;
+/// let path = sandbox.resolve("main.rs")?;
+/// assert!(path.starts_with(sandbox.root()));
+///
+/// let path = sandbox.resolve_for_write("src/lib.rs")?;
+/// assert!(sandbox.resolve_for_write("../outside.txt").is_err());
+/// # Ok(())
+/// # }
+/// ```
+///
+/// Additional roots outside the workspace can be allowed with [`Sandbox::builder`].
+#[derive(Debug, Clone)]
+pub struct Sandbox {
+    root: PathBuf,
+    /// The workspace as given, which may reach `root` through symlinks
+    workspace: PathBuf,
+    /// Extra roots accepted by [`Sandbox::resolve`] (includes write roots)
+    read_roots: Vec<PathBuf>,
+    /// Extra roots accepted by [`Sandbox::resolve_for_write`]
+    write_roots: Vec<PathBuf>,
+}
+
+impl Sandbox {
+    /// Create a sandbox rooted at `workspace`
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Sandbox)` - With the canonicalized workspace as its root
+    /// * `Err(ToolError::PathNotFound)` - If the workspace doesn't exist
+    /// * `Err(ToolError::InvalidArgument)` - If the workspace isn't a directory
+    pub fn new(workspace: impl AsRef<Path>) -> Result<Self, ToolError> {
+        Ok(Self {
+            root: canonical_dir("workspace", workspace.as_ref())?,
+            workspace: workspace.as_ref().to_path_buf(),
+            read_roots: Vec::new(),
+            write_roots: Vec::new(),
+        })
+    }
+
+    /// Start building a sandbox rooted at `workspace` with additional roots
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use a3s_tools_core::Sandbox;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let workspace = tempfile::tempdir()?;
+    /// let models = tempfile::tempdir()?;
+    /// std::fs::write(models.path().join("weights.bin"), "...")?;
+    ///
+    /// let sandbox = Sandbox::builder(workspace.path())
+    ///     .allow_read_root(models.path())
+    ///     .build()?;
+    ///
+    /// let weights = models.path().join("weights.bin");
+    /// assert!(sandbox.resolve(weights.to_str().unwrap()).is_ok());
+    /// assert!(sandbox.resolve_for_write(weights.to_str().unwrap()).is_err());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn builder(workspace: impl AsRef<Path>) -> SandboxBuilder {
+        SandboxBuilder {
+            workspace: workspace.as_ref().to_path_buf(),
+            read_roots: Vec::new(),
+            write_roots: Vec::new(),
+        }
+    }
+
+    /// Create a sandbox without requiring the workspace to exist
+    ///
+    /// Falls back to the literal workspace path if it can't be canonicalized,
+    /// matching the historical behavior of the free functions.
+    pub(crate) fn lenient(workspace: &Path) -> Self {
+        let root = workspace
+            .canonicalize()
+            .unwrap_or_else(|_| workspace.to_path_buf());
+        Self {
+            root,
+            workspace: workspace.to_path_buf(),
+            read_roots: Vec::new(),
+            write_roots: Vec::new(),
+        }
+    }
+
+    /// The canonical workspace root
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Additional canonical roots readable through this sandbox
+    pub fn read_roots(&self) -> &[PathBuf] {
+        &self.read_roots
+    }
+
+    /// Additional canonical roots writable through this sandbox
+    pub fn write_roots(&self) -> &[PathBuf] {
+        &self.write_roots
+    }
+
+    /// Ensure `canonical` is under the workspace or one of `extra_roots`
+    ///
+    /// Returns [`ToolError::PathOutsideWorkspace`] when there are no extra roots,
+    /// and [`ToolError::PathOutsideRoots`] listing every consulted root otherwise.
+    fn check_within(
+        &self,
+        canonical: &Path,
+        path: &Path,
+        extra_roots: &[PathBuf],
+    ) -> Result<(), ToolError> {
+        if is_within(canonical, &self.root)
+            || extra_roots.iter().any(|root| is_within(canonical, root))
+        {
+            return Ok(());
+        }
+
+        if extra_roots.is_empty() {
+            return Err(ToolError::PathOutsideWorkspace(path.display().to_string()));
+        }
+
+        let roots = std::iter::once(&self.root)
+            .chain(extra_roots)
+            .map(|root| root.display().to_string())
+            .collect();
+        Err(ToolError::PathOutsideRoots {
+            path: path.display().to_string(),
+            roots,
+        })
+    }
+
+    /// Fail with [`ToolError::SymlinkDenied`] if an existing component of
+    /// `resolved` below its root is a symlink
+    ///
+    /// The root prefix itself (the workspace in its canonical or as-given
+    /// spelling, or an extra root) is trusted, so an absolute path spelled
+    /// through a symlinked workspace location is treated like the equivalent
+    /// relative path. Paths under no root are checked from the filesystem root.
+    /// Walks lexically with `symlink_metadata`, stopping at the first component
+    /// that doesn't exist; `..` is applied to the walked prefix, which contains
+    /// no symlinks. `path` is the caller's spelling, used in error messages.
+    fn deny_symlinks(&self, resolved: &Path, path: &Path) -> Result<(), ToolError> {
+        let (mut current, rest) = [&self.root, &self.workspace]
+            .into_iter()
+            .chain(&self.read_roots)
+            .find_map(|root| Some((root.clone(), resolved.strip_prefix(root).ok()?)))
+            .unwrap_or((PathBuf::new(), resolved));
+        // Components walked below the root, so `..` out of it can be detected
+        let mut depth = 0usize;
+
+        for component in rest.components() {
+            match component {
+                Component::Normal(name) => {
+                    current.push(name);
+                    depth += 1;
+                    match current.symlink_metadata() {
+                        Ok(metadata) if metadata.file_type().is_symlink() => {
+                            return Err(ToolError::SymlinkDenied(path.display().to_string()));
+                        }
+                        Ok(_) => {}
+                        // Nothing below a missing component exists to be a link
+                        Err(_) => return Ok(()),
+                    }
+                }
+                Component::ParentDir if depth > 0 => {
+                    current.pop();
+                    depth -= 1;
+                }
+                Component::ParentDir => {
+                    // Leaving the root: continue from its real parent, not the
+                    // parent of an as-given alias
+                    if let Ok(canonical) = current.canonicalize() {
+                        current = canonical;
+                    }
+                    current.pop();
+                }
+                Component::CurDir => {}
+                prefix_or_root => current.push(prefix_or_root),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Resolve an existing path, ensuring it stays within the sandbox
+    ///
+    /// Same semantics as [`resolve_path`], without re-canonicalizing the workspace.
+    /// Paths under any read or write root are also accepted.
+    pub fn resolve(&self, path: &str) -> Result<PathBuf, ToolError> {
+        self.resolve_with_options(path, &ResolveOptions::default())
+    }
+
+    /// Resolve an existing path with [`ResolveOptions`]
+    ///
+    /// Same as [`Sandbox::resolve`], after applying the requested options
+    /// (e.g. `~/` expansion). Errors report `input` as given, not its expansion.
+    pub fn resolve_with_options(
+        &self,
+        input: &str,
+        options: &ResolveOptions,
+    ) -> Result<PathBuf, ToolError> {
+        let prepared = options.prepare(input)?;
+        let path = Path::new(input);
+
+        let resolved = if prepared.is_absolute() {
+            prepared.into_owned()
+        } else {
+            self.root.join(prepared)
+        };
+
+        if !options.follow_symlinks {
+            self.deny_symlinks(&resolved, path)?;
+        }
+
+        // Canonicalize to resolve .. and symlinks
+        let canonical = match resolved.canonicalize() {
+            Ok(canonical) => canonical,
+            Err(_) if exceeds_symlink_depth(&resolved) => {
+                return Err(ToolError::SymlinkLoop(input.to_string()))
+            }
+            Err(_) => {
+                // A missing path outside the sandbox is reported as outside,
+                // judged by its nearest existing ancestor
+                if let Ok(nearest) = canonicalize_for_write(&resolved, path) {
+                    self.check_within(&nearest, path, &self.read_roots)?;
+                }
+                return Err(ToolError::PathNotFound(input.to_string()));
+            }
+        };
+
+        // Security check: ensure path is within workspace or a read root
+        self.check_within(&canonical, path, &self.read_roots)?;
+
+        Ok(canonical)
+    }
+
+    /// Resolve a path for write operations, ensuring it stays within the sandbox
+    ///
+    /// Same semantics as [`resolve_path_for_write`], without re-canonicalizing the workspace.
+    /// Paths under any write root are also accepted.
+    pub fn resolve_for_write(&self, path: &str) -> Result<PathBuf, ToolError> {
+        self.resolve_for_write_with_options(path, &ResolveOptions::default())
+    }
+
+    /// Resolve a path for write operations with [`ResolveOptions`]
+    ///
+    /// Same as [`Sandbox::resolve_for_write`], after applying the requested
+    /// options (e.g. `~/` expansion). Errors report `input` as given, not its expansion.
+    pub fn resolve_for_write_with_options(
+        &self,
+        input: &str,
+        options: &ResolveOptions,
+    ) -> Result<PathBuf, ToolError> {
+        let prepared = options.prepare(input)?;
+        let path = Path::new(input);
+
+        let resolved = if prepared.is_absolute() {
+            prepared.into_owned()
+        } else {
+            self.root.join(prepared)
+        };
+
+        if !options.follow_symlinks {
+            self.deny_symlinks(&resolved, path)?;
+        }
+
+        let canonical = canonicalize_for_write(&resolved, path)?;
+
+        // Security check: ensure path is within workspace or a write root
+        self.check_within(&canonical, path, &self.write_roots)?;
+
+        Ok(canonical)
+    }
+
+    /// Convert a resolved path back to a workspace-relative one
+    ///
+    /// Same semantics as [`relativize`], without re-canonicalizing the workspace.
+    /// Paths under extra read or write roots are not under the workspace and
+    /// are rejected; display those as-is.
+    pub fn relativize(&self, resolved: &Path) -> Result<PathBuf, ToolError> {
+        let outside = || ToolError::PathOutsideWorkspace(resolved.display().to_string());
+
+        // Relative input would be canonicalized against the process cwd
+        if !resolved.is_absolute() {
+            return Err(outside());
+        }
+
+        // Canonicalize even when the root prefix matches lexically: a symlink
+        // below the root may point out, and the path may be spelled through an
+        // alias of the root (e.g. `/var` vs `/private/var`)
+        let canonical = canonicalize_for_write(resolved, resolved)?;
+        let relative = canonical
+            .strip_prefix(&self.root)
+            .map_err(|_| outside())?
+            .to_path_buf();
+
+        if relative
+            .components()
+            .any(|component| !matches!(component, Component::Normal(_) | Component::CurDir))
+        {
+            return Err(outside());
+        }
+
+        if relative.as_os_str().is_empty() {
+            Ok(PathBuf::from("."))
+        } else {
+            Ok(relative)
+        }
+    }
+
+    /// Resolve every path in `paths` with [`Sandbox::resolve`]
+    ///
+    /// Returns one result per input, in order, rather than stopping at the first error.
+    pub fn resolve_many(&self, paths: &[&str]) -> Vec<Result<PathBuf, ToolError>> {
+        paths.iter().map(|path| self.resolve(path)).collect()
+    }
+
+    /// Resolve every path in `paths` with [`Sandbox::resolve_for_write`]
+    ///
+    /// Returns one result per input, in order, rather than stopping at the first error.
+    pub fn resolve_many_for_write(&self, paths: &[&str]) -> Vec<Result<PathBuf, ToolError>> {
+        paths
+            .iter()
+            .map(|path| self.resolve_for_write(path))
+            .collect()
+    }
+}
+
+/// Canonicalize a path that may not exist yet
+///
+/// Canonicalizes the nearest existing ancestor (resolving any symlinks) and
+/// appends the remaining components lexically. Fails with
+/// [`ToolError::SymlinkLoop`] if that ancestor is part of a symlink loop, and
+/// with [`ToolError::PathOutsideWorkspace`] if no ancestor exists, the nearest
+/// one is a dangling symlink, or the non-existent suffix contains `..`.
+/// `path` is the caller's spelling, used in error messages.
+fn canonicalize_for_write(resolved: &Path, path: &Path) -> Result<PathBuf, ToolError> {
+    let outside = || ToolError::PathOutsideWorkspace(path.display().to_string());
+
+    // Find the nearest ancestor that exists (symlink_metadata so dangling links count)
+    let existing = resolved
+        .ancestors()
+        .find(|ancestor| ancestor.symlink_metadata().is_ok())
+        .ok_or_else(outside)?;
+
+    // An existing entry that can't be canonicalized is a symlink loop or a
+    // dangling symlink; the latter's target can't be verified, so refuse to
+    // write through it
+    let mut canonical = existing.canonicalize().map_err(|_| {
+        if exceeds_symlink_depth(existing) {
+            ToolError::SymlinkLoop(path.display().to_string())
+        } else {
+            outside()
+        }
+    })?;
+
+    // Append the non-existent suffix lexically
+    let suffix = resolved.strip_prefix(existing).map_err(|_| outside())?;
+    for component in suffix.components() {
+        match component {
+            Component::Normal(part) => canonical.push(part),
+            Component::CurDir => {}
+            _ => return Err(outside()),
+        }
+    }
+
+    Ok(canonical)
+}
+
+/// Whether resolving `path` needs more than [`MAX_SYMLINK_DEPTH`] symlinks
+///
+/// Walks the path one component at a time, splicing in each link's target,
+/// so it terminates on any cycle regardless of how the platform reports it.
+/// Only used to explain a failed `canonicalize`, so the common path pays nothing.
+fn exceeds_symlink_depth(path: &Path) -> bool {
+    // Components still to visit, in reverse order
+    let mut pending: Vec<PathBuf> = path
+        .components()
+        .rev()
+        .map(|c| PathBuf::from(c.as_os_str()))
+        .collect();
+    let mut current = PathBuf::new();
+    let mut followed = 0;
+
+    while let Some(next) = pending.pop() {
+        match next.components().next() {
+            Some(Component::Normal(name)) => {
+                let candidate = current.join(name);
+                match candidate.symlink_metadata() {
+                    Ok(metadata) if metadata.file_type().is_symlink() => {
+                        followed += 1;
+                        if followed > MAX_SYMLINK_DEPTH {
+                            return true;
+                        }
+                        let Ok(target) = fs::read_link(&candidate) else {
+                            return false;
+                        };
+                        if target.is_absolute() {
+                            current = PathBuf::new();
+                        }
+                        pending.extend(
+                            target
+                                .components()
+                                .rev()
+                                .map(|c| PathBuf::from(c.as_os_str())),
+                        );
+                    }
+                    Ok(_) => current = candidate,
+                    // A missing component ends the walk; that's not a loop
+                    Err(_) => return false,
+                }
+            }
+            Some(Component::ParentDir) => {
+                current.pop();
+            }
+            Some(Component::CurDir) | None => {}
+            Some(prefix_or_root) => current.push(prefix_or_root),
+        }
+    }
+
+    false
+}
+
+/// Collect per-path results, aggregating every failure into [`ToolError::PathsFailed`]
+fn all_or_nothing(
+    paths: &[&str],
+    results: Vec<Result<PathBuf, ToolError>>,
+) -> Result<Vec<PathBuf>, ToolError> {
+    let mut resolved = Vec::with_capacity(results.len());
+    let mut failures = Vec::new();
+
+    for (path, result) in paths.iter().zip(results) {
+        match result {
+            Ok(path) => resolved.push(path),
+            Err(err) => failures.push((path.to_string(), err.to_string())),
+        }
+    }
+
+    if failures.is_empty() {
+        Ok(resolved)
+    } else {
+        Err(ToolError::PathsFailed(failures))
+    }
+}
+
+/// Options controlling path resolution
+///
+/// The [`Default`] resolves paths literally and follows symlinks, matching
+/// [`resolve_path`] and [`resolve_path_for_write`].
+///
+/// # Examples
+///
+/// ```rust
+/// use a3s_tools_core::{resolve_path_for_write_with_options, ResolveOptions, ToolError};
+/// use std::path::Path;
+///
+/// let options = ResolveOptions {
+///     expand_home: true,
+///     home_dir: Some("/home/agent".into()),
+///     ..Default::default()
+/// };
+///
+/// // `~/` expands to the home directory, which is outside this workspace
+/// let result = resolve_path_for_write_with_options(Path::new("/workspace"), "~/notes.txt", &options);
+/// assert!(matches!(result, Err(ToolError::PathOutsideWorkspace(_))));
+///
+/// // Other users' home directories are never expanded
+/// let result = resolve_path_for_write_with_options(Path::new("/workspace"), "~root/.ssh", &options);
+/// assert!(matches!(result, Err(ToolError::InvalidArgument { .. })));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolveOptions {
+    /// Expand a leading `~` or `~/` to the home directory before resolution
+    ///
+    /// The expanded path is still subject to the sandbox check, so this only
+    /// succeeds when the home directory is inside the workspace (or an allowed root).
+    /// `~user/...` forms are rejected with [`ToolError::InvalidArgument`].
+    pub expand_home: bool,
+
+    /// Home directory used for expansion
+    ///
+    /// Defaults to `$HOME` (`%USERPROFILE%` on Windows) when `None`.
+    pub home_dir: Option<PathBuf>,
+
+    /// Follow symlinks that resolve inside the sandbox (default `true`)
+    ///
+    /// When `false` (strict mode), resolution fails with
+    /// [`ToolError::SymlinkDenied`] if any existing component of the path
+    /// below the workspace (or an extra root) is a symlink, wherever it points.
+    /// The root itself may be reached through symlinks. A link inside the
+    /// workspace can be re-pointed between resolution and use, so strict mode
+    /// closes that gap.
+    pub follow_symlinks: bool,
+}
+
+impl Default for ResolveOptions {
+    fn default() -> Self {
+        Self {
+            expand_home: false,
+            home_dir: None,
+            follow_symlinks: true,
+        }
+    }
+}
+
+impl ResolveOptions {
+    /// Apply the options to a raw input path
+    fn prepare<'a>(&self, path: &'a str) -> Result<Cow<'a, Path>, ToolError> {
+        if !self.expand_home {
+            return Ok(Cow::Borrowed(Path::new(path)));
+        }
+
+        let Some(rest) = path.strip_prefix('~') else {
+            return Ok(Cow::Borrowed(Path::new(path)));
+        };
+
+        let rest = if rest.is_empty() {
+            rest
+        } else if let Some(rest) = rest
+            .strip_prefix('/')
+            .or_else(|| rest.strip_prefix(std::path::MAIN_SEPARATOR))
+        {
+            rest
+        } else {
+            let user = rest
+                .split(['/', std::path::MAIN_SEPARATOR])
+                .next()
+                .unwrap_or(rest);
+            return Err(ToolError::invalid_arg(
+                "path",
+                format!("cannot expand '~{}': only '~/' is supported", user),
+            ));
+        };
+
+        let home = self.home_dir.clone().or_else(home_dir).ok_or_else(|| {
+            ToolError::invalid_arg("path", "cannot expand '~': home directory is not set")
+        })?;
+
+        Ok(Cow::Owned(home.join(rest)))
+    }
+}
+
+/// Whether `path` is `root` or inside it
+///
+/// On Windows, `canonicalize` returns verbatim paths (`\\?\C:\...`) while a
+/// workspace that couldn't be canonicalized keeps its plain form (`C:\...`),
+/// so both sides are normalized with [`normalize_windows_path`] first.
+pub(crate) fn is_within(path: &Path, root: &Path) -> bool {
+    if cfg!(windows) {
+        windows_path_starts_with(&path.to_string_lossy(), &root.to_string_lossy())
+    } else {
+        path.starts_with(root)
+    }
+}
+
+/// Normalize a Windows path string for comparison
+///
+/// - Converts `/` separators to `\` and collapses repeated separators
+/// - Strips the verbatim prefix (`\\?\C:\` → `C:\`, `\\?\UNC\server` → `\\server`)
+/// - Uppercases the drive letter
+/// - Removes trailing separators (except after a bare drive root)
+///
+/// This is a pure string transformation so it can be tested on every platform.
+fn normalize_windows_path(path: &str) -> String {
+    let path = path.replace('/', "\\");
+
+    let (unc, rest) = if let Some(rest) = path.strip_prefix("\\\\?\\UNC\\") {
+        (true, rest)
+    } else if let Some(rest) = path.strip_prefix("\\\\?\\") {
+        (false, rest)
+    } else if let Some(rest) = path.strip_prefix("\\\\") {
+        (true, rest)
+    } else {
+        (false, path.as_str())
+    };
+
+    let mut normalized = String::with_capacity(rest.len() + 2);
+    if unc {
+        normalized.push_str("\\\\");
+    } else if rest.starts_with('\\') {
+        normalized.push('\\');
+    }
+
+    let components: Vec<&str> = rest.split('\\').filter(|c| !c.is_empty()).collect();
+    for (i, component) in components.iter().enumerate() {
+        if i > 0 {
+            normalized.push('\\');
+        }
+        let is_drive = i == 0
+            && !unc
+            && component.len() == 2
+            && component.as_bytes()[0].is_ascii_alphabetic()
+            && component.as_bytes()[1] == b':';
+        if is_drive {
+            normalized.push_str(&component.to_ascii_uppercase());
+            if components.len() == 1 {
+                normalized.push('\\');
+            }
+        } else {
+            normalized.push_str(component);
+        }
+    }
+
+    normalized
+}
+
+/// Component-wise prefix check on normalized Windows path strings
+fn windows_path_starts_with(path: &str, root: &str) -> bool {
+    let path = normalize_windows_path(path);
+    let root = normalize_windows_path(root);
+
+    let mut path_components = path.split('\\').filter(|c| !c.is_empty());
+    root.split('\\')
+        .filter(|c| !c.is_empty())
+        .all(|root_component| path_components.next() == Some(root_component))
+        && path.starts_with("\\\\") == root.starts_with("\\\\")
+}
+
+/// The current user's home directory from the environment
+fn home_dir() -> Option<PathBuf> {
+    let var = if cfg!(windows) { "USERPROFILE" } else { "HOME" };
+    std::env::var_os(var)
+        .filter(|home| !home.is_empty())
+        .map(PathBuf::from)
+}
+
+/// Builder for a [`Sandbox`] with roots beyond the primary workspace
+///
+/// Read roots are accepted by [`Sandbox::resolve`] only. Write roots are
+/// accepted by both [`Sandbox::resolve`] and [`Sandbox::resolve_for_write`].
+/// Relative paths are always resolved against the workspace.
+#[derive(Debug, Clone)]
+pub struct SandboxBuilder {
+    workspace: PathBuf,
+    read_roots: Vec<PathBuf>,
+    write_roots: Vec<PathBuf>,
+}
+
+impl SandboxBuilder {
+    /// Allow reading under `root` (e.g. a shared read-only cache like `/opt/models`)
+    pub fn allow_read_root(mut self, root: impl AsRef<Path>) -> Self {
+        self.read_roots.push(root.as_ref().to_path_buf());
+        self
+    }
+
+    /// Allow reading and writing under `root`
+    pub fn allow_write_root(mut self, root: impl AsRef<Path>) -> Self {
+        self.write_roots.push(root.as_ref().to_path_buf());
+        self
+    }
+
+    /// Canonicalize the workspace and all roots, and build the sandbox
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(Sandbox)` - With every root canonicalized
+    /// * `Err(ToolError::PathNotFound)` - If the workspace or a root doesn't exist
+    /// * `Err(ToolError::InvalidArgument)` - If the workspace or a root isn't a directory
+    pub fn build(self) -> Result<Sandbox, ToolError> {
+        let root = canonical_dir("workspace", &self.workspace)?;
+
+        let write_roots = self
+            .write_roots
+            .iter()
+            .map(|root| canonical_dir("write_root", root))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut read_roots = self
+            .read_roots
+            .iter()
+            .map(|root| canonical_dir("read_root", root))
+            .collect::<Result<Vec<_>, _>>()?;
+        read_roots.extend(write_roots.iter().cloned());
+
+        Ok(Sandbox {
+            root,
+            workspace: self.workspace,
+            read_roots,
+            write_roots,
+        })
+    }
+}
+
+/// Canonicalize `dir`, requiring it to exist and be a directory
+fn canonical_dir(name: &str, dir: &Path) -> Result<PathBuf, ToolError> {
+    let canonical = dir
+        .canonicalize()
+        .map_err(|_| ToolError::PathNotFound(dir.display().to_string()))?;
+
+    if !canonical.is_dir() {
+        return Err(ToolError::invalid_arg(
+            name,
+            format!("'{}' is not a directory", dir.display()),
+        ));
+    }
+
+    Ok(canonical)
+}
 
 /// Resolve a path relative to workspace, ensuring it stays within sandbox
 ///
@@ -49,7 +770,10 @@ use std::path::{Path, PathBuf};
 ///
 /// * `Ok(PathBuf)` - The resolved canonical path within workspace
 /// * `Err(ToolError::PathNotFound)` - If the path doesn't exist
-/// * `Err(ToolError::PathOutsideWorkspace)` - If the path is outside workspace
+/// * `Err(ToolError::PathOutsideWorkspace)` - If the path is outside workspace,
+///   whether or not it exists
+/// * `Err(ToolError::SymlinkLoop)` - If resolving the path needs more than
+///   [`MAX_SYMLINK_DEPTH`](crate::MAX_SYMLINK_DEPTH) symlinks (e.g. `a -> b`, `b -> a`)
 ///
 /// # Security
 ///
@@ -78,30 +802,22 @@ use std::path::{Path, PathBuf};
 /// # }
 /// ```
 pub fn resolve_path(workspace: &Path, path: &str) -> Result<PathBuf, ToolError> {
-    let path = Path::new(path);
-
-    let resolved = if path.is_absolute() {
-        path.to_path_buf()
-    } else {
-        workspace.join(path)
-    };
-
-    // Canonicalize to resolve .. and symlinks
-    let canonical = resolved
-        .canonicalize()
-        .map_err(|_| ToolError::PathNotFound(path.display().to_string()))?;
-
-    // Canonicalize workspace for comparison (handles symlinks like /var -> /private/var on macOS)
-    let canonical_workspace = workspace
-        .canonicalize()
-        .unwrap_or_else(|_| workspace.to_path_buf());
-
-    // Security check: ensure path is within workspace
-    if !canonical.starts_with(&canonical_workspace) {
-        return Err(ToolError::PathOutsideWorkspace(path.display().to_string()));
-    }
+    Sandbox::lenient(workspace).resolve(path)
+}
 
-    Ok(canonical)
+/// Resolve a path relative to workspace with [`ResolveOptions`]
+///
+/// Same as [`resolve_path`], after applying the requested options.
+/// With `expand_home`, `~/project/file.txt` is resolved under the home
+/// directory and then checked against the workspace as usual.
+/// With `follow_symlinks: false`, any symlink component (even one pointing
+/// inside the workspace) fails with [`ToolError::SymlinkDenied`].
+pub fn resolve_path_with_options(
+    workspace: &Path,
+    path: &str,
+    options: &ResolveOptions,
+) -> Result<PathBuf, ToolError> {
+    Sandbox::lenient(workspace).resolve_with_options(path, options)
 }
 
 /// Resolve a path for write operations (allows non-existent files)
@@ -119,12 +835,19 @@ pub fn resolve_path(workspace: &Path, path: &str) -> Result<PathBuf, ToolError>
 ///
 /// * `Ok(PathBuf)` - The resolved path within workspace
 /// * `Err(ToolError::PathOutsideWorkspace)` - If the path would be outside workspace
+/// * `Err(ToolError::SymlinkLoop)` - If its nearest existing ancestor is part of a symlink loop
 ///
 /// # Security
 ///
 /// For write operations, we can't canonicalize non-existent paths.
-/// Instead, we verify the parent directory is within workspace.
-/// This prevents creating files outside the workspace boundary.
+/// Instead, we walk up the ancestor chain to the nearest path that exists,
+/// canonicalize it (resolving any symlinks), and append the remaining
+/// non-existent components lexically. The result must be within the workspace.
+///
+/// This catches symlinked intermediate directories that point outside the
+/// workspace even when deeper components don't exist yet (e.g. `link/new/file.txt`
+/// where `link -> /tmp`). Any `..` component in the non-existent suffix is
+/// rejected, since it can't be resolved safely without the directory existing.
 ///
 /// # Examples
 ///
@@ -145,34 +868,138 @@ pub fn resolve_path(workspace: &Path, path: &str) -> Result<PathBuf, ToolError>
 /// # }
 /// ```
 pub fn resolve_path_for_write(workspace: &Path, path: &str) -> Result<PathBuf, ToolError> {
-    let path = Path::new(path);
+    Sandbox::lenient(workspace).resolve_for_write(path)
+}
 
-    let resolved = if path.is_absolute() {
-        path.to_path_buf()
-    } else {
-        workspace.join(path)
-    };
+/// Resolve a path for write operations with [`ResolveOptions`]
+///
+/// Same as [`resolve_path_for_write`], after applying the requested options.
+/// With `follow_symlinks: false`, a symlink anywhere along the existing part
+/// of the path fails with [`ToolError::SymlinkDenied`].
+pub fn resolve_path_for_write_with_options(
+    workspace: &Path,
+    path: &str,
+    options: &ResolveOptions,
+) -> Result<PathBuf, ToolError> {
+    Sandbox::lenient(workspace).resolve_for_write_with_options(path, options)
+}
 
-    // Canonicalize workspace for comparison
-    let canonical_workspace = workspace
-        .canonicalize()
-        .unwrap_or_else(|_| workspace.to_path_buf());
+/// Convert a resolved path back to workspace-relative form
+///
+/// Tools that echo paths back to the model should show `src/main.rs` rather
+/// than the canonical `/private/var/folders/.../workspace/src/main.rs`, which
+/// leaks host directory layout and wastes tokens.
+///
+/// # Arguments
+///
+/// * `workspace` - The workspace root directory (sandbox boundary)
+/// * `resolved` - A path returned by [`resolve_path`] or [`resolve_path_for_write`]
+///
+/// # Returns
+///
+/// * `Ok(PathBuf)` - The path relative to the canonical workspace root, or `.`
+///   for the root itself
+/// * `Err(ToolError::PathOutsideWorkspace)` - If the path isn't under the workspace
+///
+/// # Security
+///
+/// The workspace may be given in un-canonicalized form (e.g. `/var/...` on macOS,
+/// where the canonical root is `/private/var/...`), and `resolved` may be spelled
+/// either way. Every path is canonicalized through its nearest existing
+/// ancestor before comparing, so a path that only lexically looks like it's
+/// inside (e.g. `root/escape/file` where `escape` is a symlink pointing out)
+/// is rejected. A path through a symlink that stays inside is reported by its
+/// target (e.g. `real/file` rather than `alias/file`).
+///
+/// # Examples
+///
+/// ```rust
+/// use a3s_tools_core::{relativize, resolve_path};
+/// use std::path::Path;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let dir = tempfile::tempdir()?;
+/// std::fs::create_dir_all(dir.path().join("src"))?;
+/// std::fs::write(dir.path().join("src/main.rs"), "fn main() {}")?;
+///
+/// let resolved = resolve_path(dir.path(), "src/main.rs")?;
+/// assert_eq!(relativize(dir.path(), &resolved)?, Path::new("src/main.rs"));
+/// assert_eq!(relativize(dir.path(), dir.path())?, Path::new("."));
+/// assert!(relativize(dir.path(), Path::new("/etc/passwd")).is_err());
+/// # Ok(())
+/// # }
+/// ```
+pub fn relativize(workspace: &Path, resolved: &Path) -> Result<PathBuf, ToolError> {
+    Sandbox::lenient(workspace).relativize(resolved)
+}
 
-    // For write operations, check that the parent directory is within workspace
-    if let Some(parent) = resolved.parent() {
-        let canonical_parent = parent
-            .canonicalize()
-            .unwrap_or_else(|_| parent.to_path_buf());
+/// Resolve a batch of paths for reading, reporting a result per path
+///
+/// Like calling [`resolve_path`] on each entry, but canonicalizes the workspace
+/// only once and never stops early, so callers can report every bad path.
+///
+/// # Examples
+///
+/// ```rust
+/// use a3s_tools_core::resolve_paths;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let dir = tempfile::tempdir()?;
+/// std::fs::write(dir.path().join("a.txt"), "a")?;
+///
+/// let results = resolve_paths(dir.path(), &["a.txt", "missing.txt", "/etc/passwd"]);
+/// assert!(results[0].is_ok());
+/// assert!(results[1].is_err());
+/// assert!(results[2].is_err());
+/// # Ok(())
+/// # }
+/// ```
+pub fn resolve_paths(workspace: &Path, paths: &[&str]) -> Vec<Result<PathBuf, ToolError>> {
+    Sandbox::lenient(workspace).resolve_many(paths)
+}
 
-        // Allow if parent is workspace or within workspace
-        if canonical_parent != canonical_workspace
-            && !canonical_parent.starts_with(&canonical_workspace)
-        {
-            return Err(ToolError::PathOutsideWorkspace(path.display().to_string()));
-        }
-    }
+/// Resolve a batch of paths for writing, reporting a result per path
+///
+/// Like calling [`resolve_path_for_write`] on each entry, but canonicalizes the
+/// workspace only once and never stops early.
+pub fn resolve_paths_for_write(
+    workspace: &Path,
+    paths: &[&str],
+) -> Vec<Result<PathBuf, ToolError>> {
+    Sandbox::lenient(workspace).resolve_many_for_write(paths)
+}
+
+/// Resolve a batch of paths for reading, failing if any path fails
+///
+/// # Returns
+///
+/// * `Ok(Vec<PathBuf>)` - Every resolved path, in input order
+/// * `Err(ToolError::PathsFailed)` - Listing every path that failed and why
+///
+/// # Examples
+///
+/// ```rust
+/// use a3s_tools_core::{resolve_paths_all_or_nothing, ToolError};
+///
+/// let dir = tempfile::tempdir().unwrap();
+/// let err = resolve_paths_all_or_nothing(dir.path(), &["a.txt", "b.txt"]).unwrap_err();
+/// assert!(matches!(&err, ToolError::PathsFailed(failures) if failures.len() == 2));
+/// ```
+pub fn resolve_paths_all_or_nothing(
+    workspace: &Path,
+    paths: &[&str],
+) -> Result<Vec<PathBuf>, ToolError> {
+    all_or_nothing(paths, resolve_paths(workspace, paths))
+}
 
-    Ok(resolved)
+/// Resolve a batch of paths for writing, failing if any path fails
+///
+/// Write counterpart of [`resolve_paths_all_or_nothing`].
+pub fn resolve_paths_for_write_all_or_nothing(
+    workspace: &Path,
+    paths: &[&str],
+) -> Result<Vec<PathBuf>, ToolError> {
+    all_or_nothing(paths, resolve_paths_for_write(workspace, paths))
 }
 
 #[cfg(test)]
@@ -250,26 +1077,109 @@ mod tests {
         // Should succeed for non-existent file in workspace
         let result = resolve_path_for_write(workspace, "new_file.txt");
         assert!(result.is_ok());
-        assert_eq!(result.unwrap(), workspace.join("new_file.txt"));
+        assert_eq!(
+            result.unwrap(),
+            workspace.canonicalize().unwrap().join("new_file.txt")
+        );
     }
 
     #[test]
-    fn test_resolve_path_for_write_reject_outside() {
+    fn test_resolve_path_for_write_deep_new_path() {
         let temp_dir = tempfile::tempdir().unwrap();
         let workspace = temp_dir.path();
 
-        // Should reject path outside workspace
-        let result = resolve_path_for_write(workspace, "/tmp/outside.txt");
-        assert!(matches!(result, Err(ToolError::PathOutsideWorkspace(_))));
+        let result = resolve_path_for_write(workspace, "a/b/c/new_file.txt");
+        assert_eq!(
+            result.unwrap(),
+            workspace.canonicalize().unwrap().join("a/b/c/new_file.txt")
+        );
     }
 
     #[test]
-    fn test_resolve_nested_path() {
+    fn test_resolve_path_for_write_reject_dotdot_in_new_suffix() {
         let temp_dir = tempfile::tempdir().unwrap();
         let workspace = temp_dir.path();
 
-        // Create nested directory and file
-        let nested_dir = workspace.join("subdir");
+        let result = resolve_path_for_write(workspace, "missing/../../outside.txt");
+        assert!(matches!(result, Err(ToolError::PathOutsideWorkspace(_))));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_resolve_path_for_write_reject_symlinked_intermediate_dir() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let workspace = temp_dir.path();
+        let outside = tempfile::tempdir().unwrap();
+
+        // link -> directory outside the workspace; link/b/c does not exist
+        std::os::unix::fs::symlink(outside.path(), workspace.join("link")).unwrap();
+
+        let result = resolve_path_for_write(workspace, "link/b/c/deep.txt");
+        assert!(matches!(result, Err(ToolError::PathOutsideWorkspace(_))));
+
+        let result = resolve_path_for_write(workspace, "link/file.txt");
+        assert!(matches!(result, Err(ToolError::PathOutsideWorkspace(_))));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_resolve_path_for_write_reject_symlink_to_tmp() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let workspace = temp_dir.path();
+
+        std::os::unix::fs::symlink("/tmp", workspace.join("tmp_link")).unwrap();
+
+        let result = resolve_path_for_write(workspace, "tmp_link/x/y/z/file.txt");
+        assert!(matches!(result, Err(ToolError::PathOutsideWorkspace(_))));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_resolve_path_for_write_allow_symlink_inside_workspace() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let workspace = temp_dir.path();
+
+        fs::create_dir(workspace.join("real")).unwrap();
+        std::os::unix::fs::symlink(workspace.join("real"), workspace.join("alias")).unwrap();
+
+        let result = resolve_path_for_write(workspace, "alias/new/file.txt");
+        assert_eq!(
+            result.unwrap(),
+            workspace.canonicalize().unwrap().join("real/new/file.txt")
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_resolve_path_for_write_reject_dangling_symlink() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let workspace = temp_dir.path();
+        let outside = tempfile::tempdir().unwrap();
+
+        std::os::unix::fs::symlink(outside.path().join("missing"), workspace.join("dangling"))
+            .unwrap();
+
+        let result = resolve_path_for_write(workspace, "dangling/file.txt");
+        assert!(matches!(result, Err(ToolError::PathOutsideWorkspace(_))));
+    }
+
+    #[test]
+    fn test_resolve_path_for_write_reject_outside() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let workspace = temp_dir.path();
+
+        // Should reject path outside workspace
+        let result = resolve_path_for_write(workspace, "/tmp/outside.txt");
+        assert!(matches!(result, Err(ToolError::PathOutsideWorkspace(_))));
+    }
+
+    #[test]
+    fn test_resolve_nested_path() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let workspace = temp_dir.path();
+
+        // Create nested directory and file
+        let nested_dir = workspace.join("subdir");
         fs::create_dir(&nested_dir).unwrap();
         let nested_file = nested_dir.join("file.txt");
         fs::write(&nested_file, "content").unwrap();
@@ -278,4 +1188,760 @@ mod tests {
         assert!(result.is_ok());
         assert_eq!(result.unwrap(), nested_file.canonicalize().unwrap());
     }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_resolve_symlink_loop() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let workspace = temp_dir.path();
+        std::os::unix::fs::symlink(workspace.join("b"), workspace.join("a")).unwrap();
+        std::os::unix::fs::symlink(workspace.join("a"), workspace.join("b")).unwrap();
+        std::os::unix::fs::symlink("self", workspace.join("self")).unwrap();
+
+        for path in ["a", "b/file.txt", "self"] {
+            let result = resolve_path(workspace, path);
+            assert!(
+                matches!(&result, Err(ToolError::SymlinkLoop(p)) if p == path),
+                "{path}: {result:?}"
+            );
+        }
+
+        // A plain missing path is still PathNotFound
+        let result = resolve_path(workspace, "missing/file.txt");
+        assert!(matches!(result, Err(ToolError::PathNotFound(_))));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_resolve_for_write_symlink_loop() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let workspace = temp_dir.path();
+        std::os::unix::fs::symlink(workspace.join("b"), workspace.join("a")).unwrap();
+        std::os::unix::fs::symlink(workspace.join("a"), workspace.join("b")).unwrap();
+
+        let result = resolve_path_for_write(workspace, "a/new/file.txt");
+        assert!(matches!(result, Err(ToolError::SymlinkLoop(_))));
+        let result = resolve_path_for_write(workspace, "b");
+        assert!(matches!(result, Err(ToolError::SymlinkLoop(_))));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_exceeds_symlink_depth_long_chain() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let workspace = temp_dir.path().canonicalize().unwrap();
+        fs::write(workspace.join("target.txt"), "x").unwrap();
+
+        // link0 -> link1 -> ... -> linkN -> target.txt
+        let chain = |len: usize| {
+            for i in 0..len {
+                let next = if i + 1 == len {
+                    "target.txt".to_string()
+                } else {
+                    format!("link{}", i + 1)
+                };
+                let _ = fs::remove_file(workspace.join(format!("link{i}")));
+                std::os::unix::fs::symlink(next, workspace.join(format!("link{i}"))).unwrap();
+            }
+        };
+
+        chain(MAX_SYMLINK_DEPTH);
+        assert!(!exceeds_symlink_depth(&workspace.join("link0")));
+        chain(MAX_SYMLINK_DEPTH + 1);
+        assert!(exceeds_symlink_depth(&workspace.join("link0")));
+
+        // Relative `..` inside link targets is resolved against the link's directory
+        fs::create_dir(workspace.join("dir")).unwrap();
+        std::os::unix::fs::symlink("../dir/up", workspace.join("dir/up")).unwrap();
+        assert!(exceeds_symlink_depth(&workspace.join("dir/up")));
+        assert!(!exceeds_symlink_depth(&workspace.join("dir/missing")));
+    }
+
+    fn strict_options() -> ResolveOptions {
+        ResolveOptions {
+            follow_symlinks: false,
+            ..Default::default()
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_strict_rejects_symlinked_file() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let workspace = temp_dir.path();
+
+        fs::write(workspace.join("real.txt"), "content").unwrap();
+        std::os::unix::fs::symlink(workspace.join("real.txt"), workspace.join("link.txt")).unwrap();
+
+        let result = resolve_path_with_options(workspace, "link.txt", &strict_options());
+        assert!(matches!(result, Err(ToolError::SymlinkDenied(p)) if p == "link.txt"));
+
+        let result = resolve_path_for_write_with_options(workspace, "link.txt", &strict_options());
+        assert!(matches!(result, Err(ToolError::SymlinkDenied(_))));
+
+        // Plain files are unaffected
+        let resolved = resolve_path_with_options(workspace, "real.txt", &strict_options());
+        assert_eq!(
+            resolved.unwrap(),
+            workspace.canonicalize().unwrap().join("real.txt")
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_strict_rejects_symlinked_intermediate_dir() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let workspace = temp_dir.path();
+
+        fs::create_dir_all(workspace.join("real/sub")).unwrap();
+        fs::write(workspace.join("real/sub/file.txt"), "").unwrap();
+        std::os::unix::fs::symlink(workspace.join("real"), workspace.join("alias")).unwrap();
+
+        let sandbox = Sandbox::new(workspace).unwrap();
+        let result = sandbox.resolve_with_options("alias/sub/file.txt", &strict_options());
+        assert!(matches!(result, Err(ToolError::SymlinkDenied(_))));
+
+        // Also for writes below the link that don't exist yet
+        let result =
+            sandbox.resolve_for_write_with_options("alias/new/file.txt", &strict_options());
+        assert!(matches!(result, Err(ToolError::SymlinkDenied(_))));
+
+        // `..` past a real directory and into new components is fine
+        let result =
+            sandbox.resolve_for_write_with_options("real/sub/../new.txt", &strict_options());
+        assert_eq!(result.unwrap(), sandbox.root().join("real/new.txt"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_strict_allows_symlinked_workspace_root() {
+        // Same shape as macOS, where /var is a symlink to /private/var
+        let temp_dir = tempfile::tempdir().unwrap();
+        let real = temp_dir.path().join("private/var/workspace");
+        fs::create_dir_all(real.join("src")).unwrap();
+        fs::write(real.join("src/main.rs"), "").unwrap();
+        fs::write(real.join("target.txt"), "").unwrap();
+        std::os::unix::fs::symlink(real.join("target.txt"), real.join("link.txt")).unwrap();
+        std::os::unix::fs::symlink(
+            temp_dir.path().join("private/var"),
+            temp_dir.path().join("var"),
+        )
+        .unwrap();
+        let workspace = temp_dir.path().join("var/workspace");
+        let expected = real.canonicalize().unwrap().join("src/main.rs");
+
+        for input in [
+            "src/main.rs".to_string(),
+            workspace.join("src/main.rs").display().to_string(),
+            real.join("src/main.rs").display().to_string(),
+        ] {
+            let resolved = resolve_path_with_options(&workspace, &input, &strict_options());
+            assert_eq!(resolved.unwrap(), expected, "{input}");
+            let resolved =
+                resolve_path_for_write_with_options(&workspace, &input, &strict_options());
+            assert_eq!(resolved.unwrap(), expected, "{input}");
+        }
+
+        // Links below the root are still refused, however the root is spelled
+        let input = workspace.join("link.txt").display().to_string();
+        let result = resolve_path_with_options(&workspace, &input, &strict_options());
+        assert!(matches!(result, Err(ToolError::SymlinkDenied(_))));
+
+        // Absolute paths under no root are checked in full
+        let sandbox = Sandbox::new(real.join("src")).unwrap();
+        let input = workspace.join("src/main.rs").display().to_string();
+        let result = sandbox.resolve_with_options(&input, &strict_options());
+        assert!(matches!(result, Err(ToolError::SymlinkDenied(_))));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_default_follows_symlinks_inside_workspace() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let workspace = temp_dir.path();
+
+        fs::create_dir(workspace.join("real")).unwrap();
+        fs::write(workspace.join("real/file.txt"), "").unwrap();
+        std::os::unix::fs::symlink(workspace.join("real"), workspace.join("alias")).unwrap();
+
+        assert!(ResolveOptions::default().follow_symlinks);
+        let expected = workspace.canonicalize().unwrap().join("real/file.txt");
+        let options = ResolveOptions::default();
+        assert_eq!(
+            resolve_path_with_options(workspace, "alias/file.txt", &options).unwrap(),
+            expected
+        );
+        assert_eq!(
+            resolve_path_for_write_with_options(workspace, "alias/file.txt", &options).unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_sandbox_new_missing_workspace() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let missing = temp_dir.path().join("missing");
+
+        let result = Sandbox::new(&missing);
+        assert!(matches!(result, Err(ToolError::PathNotFound(_))));
+    }
+
+    #[test]
+    fn test_sandbox_new_workspace_is_file() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let file = temp_dir.path().join("file.txt");
+        fs::write(&file, "hello").unwrap();
+
+        let result = Sandbox::new(&file);
+        assert!(matches!(result, Err(ToolError::InvalidArgument { .. })));
+    }
+
+    #[test]
+    fn test_sandbox_resolve_matches_free_functions() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let workspace = temp_dir.path();
+        fs::write(workspace.join("test.txt"), "hello").unwrap();
+
+        let sandbox = Sandbox::new(workspace).unwrap();
+        assert_eq!(sandbox.root(), workspace.canonicalize().unwrap());
+
+        assert_eq!(
+            sandbox.resolve("test.txt").unwrap(),
+            resolve_path(workspace, "test.txt").unwrap()
+        );
+        assert_eq!(
+            sandbox.resolve_for_write("new/file.txt").unwrap(),
+            resolve_path_for_write(workspace, "new/file.txt").unwrap()
+        );
+        assert!(matches!(
+            sandbox.resolve("/etc/passwd"),
+            Err(ToolError::PathOutsideWorkspace(_))
+        ));
+        assert!(matches!(
+            sandbox.resolve_for_write("../outside.txt"),
+            Err(ToolError::PathOutsideWorkspace(_))
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_sandbox_root_canonicalized_once() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let first = temp_dir.path().join("first");
+        let second = temp_dir.path().join("second");
+        fs::create_dir(&first).unwrap();
+        fs::create_dir(&second).unwrap();
+        fs::write(first.join("a.txt"), "a").unwrap();
+        fs::write(second.join("b.txt"), "b").unwrap();
+
+        let link = temp_dir.path().join("workspace");
+        std::os::unix::fs::symlink(&first, &link).unwrap();
+        let sandbox = Sandbox::new(&link).unwrap();
+
+        // Re-point the workspace link; a per-call canonicalize would follow it
+        fs::remove_file(&link).unwrap();
+        std::os::unix::fs::symlink(&second, &link).unwrap();
+
+        assert_eq!(sandbox.root(), first.canonicalize().unwrap());
+        assert!(sandbox.resolve("a.txt").is_ok());
+        assert!(matches!(
+            sandbox.resolve(link.join("b.txt").to_str().unwrap()),
+            Err(ToolError::PathOutsideWorkspace(_))
+        ));
+    }
+
+    #[test]
+    fn test_sandbox_read_root() {
+        let workspace = tempfile::tempdir().unwrap();
+        let cache = tempfile::tempdir().unwrap();
+        let cached = cache.path().join("model.bin");
+        fs::write(&cached, "weights").unwrap();
+        let cached = cached.to_str().unwrap();
+
+        let sandbox = Sandbox::builder(workspace.path())
+            .allow_read_root(cache.path())
+            .build()
+            .unwrap();
+
+        assert!(sandbox.resolve(cached).is_ok());
+
+        // Read roots don't grant write access
+        let result = sandbox.resolve_for_write(cached);
+        assert!(matches!(result, Err(ToolError::PathOutsideWorkspace(_))));
+    }
+
+    #[test]
+    fn test_sandbox_write_root() {
+        let workspace = tempfile::tempdir().unwrap();
+        let shared = tempfile::tempdir().unwrap();
+        let target = shared.path().join("out/result.txt");
+
+        let sandbox = Sandbox::builder(workspace.path())
+            .allow_write_root(shared.path())
+            .build()
+            .unwrap();
+
+        let resolved = sandbox.resolve_for_write(target.to_str().unwrap()).unwrap();
+        assert!(resolved.starts_with(shared.path().canonicalize().unwrap()));
+
+        // Write roots are readable too
+        fs::write(shared.path().join("existing.txt"), "x").unwrap();
+        let existing = shared.path().join("existing.txt");
+        assert!(sandbox.resolve(existing.to_str().unwrap()).is_ok());
+    }
+
+    #[test]
+    fn test_sandbox_rejection_lists_roots() {
+        let workspace = tempfile::tempdir().unwrap();
+        let cache = tempfile::tempdir().unwrap();
+        let other = tempfile::tempdir().unwrap();
+        let secret = other.path().join("secret.txt");
+        fs::write(&secret, "secret").unwrap();
+
+        let sandbox = Sandbox::builder(workspace.path())
+            .allow_read_root(cache.path())
+            .build()
+            .unwrap();
+
+        let err = sandbox.resolve(secret.to_str().unwrap()).unwrap_err();
+        match &err {
+            ToolError::PathOutsideRoots { roots, .. } => {
+                assert_eq!(
+                    roots,
+                    &vec![
+                        sandbox.root().display().to_string(),
+                        cache.path().canonicalize().unwrap().display().to_string(),
+                    ]
+                );
+            }
+            other => panic!("unexpected error: {other:?}"),
+        }
+        let message = err.to_string();
+        assert!(message.contains(&sandbox.root().display().to_string()));
+        assert!(message.contains(&cache.path().canonicalize().unwrap().display().to_string()));
+    }
+
+    #[test]
+    fn test_sandbox_overlapping_roots() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let outer = temp_dir.path().join("outer");
+        let inner = outer.join("inner");
+        let workspace = temp_dir.path().join("workspace");
+        fs::create_dir_all(&inner).unwrap();
+        fs::create_dir(&workspace).unwrap();
+        fs::write(outer.join("a.txt"), "a").unwrap();
+        fs::write(inner.join("b.txt"), "b").unwrap();
+
+        // Inner root is redundant but harmless; outer is read-only, inner writable
+        let sandbox = Sandbox::builder(&workspace)
+            .allow_read_root(&outer)
+            .allow_write_root(&inner)
+            .allow_read_root(&inner)
+            .build()
+            .unwrap();
+
+        assert!(sandbox
+            .resolve(outer.join("a.txt").to_str().unwrap())
+            .is_ok());
+        assert!(sandbox
+            .resolve(inner.join("b.txt").to_str().unwrap())
+            .is_ok());
+        assert!(sandbox
+            .resolve_for_write(inner.join("new.txt").to_str().unwrap())
+            .is_ok());
+        assert!(matches!(
+            sandbox.resolve_for_write(outer.join("new.txt").to_str().unwrap()),
+            Err(ToolError::PathOutsideRoots { .. })
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_sandbox_symlinked_root() {
+        let workspace = tempfile::tempdir().unwrap();
+        let temp_dir = tempfile::tempdir().unwrap();
+        let real = temp_dir.path().join("real");
+        let link = temp_dir.path().join("link");
+        fs::create_dir(&real).unwrap();
+        fs::write(real.join("data.txt"), "data").unwrap();
+        std::os::unix::fs::symlink(&real, &link).unwrap();
+
+        let sandbox = Sandbox::builder(workspace.path())
+            .allow_read_root(&link)
+            .build()
+            .unwrap();
+
+        // Stored canonicalized, so both the link and the real path are accepted
+        assert_eq!(sandbox.read_roots(), &[real.canonicalize().unwrap()]);
+        assert!(sandbox
+            .resolve(link.join("data.txt").to_str().unwrap())
+            .is_ok());
+        assert!(sandbox
+            .resolve(real.join("data.txt").to_str().unwrap())
+            .is_ok());
+    }
+
+    #[test]
+    fn test_sandbox_builder_missing_root() {
+        let workspace = tempfile::tempdir().unwrap();
+        let missing = workspace.path().join("missing");
+
+        let result = Sandbox::builder(workspace.path())
+            .allow_read_root(&missing)
+            .build();
+        assert!(matches!(result, Err(ToolError::PathNotFound(_))));
+    }
+
+    fn home_options(home: &Path) -> ResolveOptions {
+        ResolveOptions {
+            expand_home: true,
+            home_dir: Some(home.to_path_buf()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_tilde_literal_by_default() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let workspace = temp_dir.path();
+        fs::create_dir(workspace.join("~")).unwrap();
+        fs::write(workspace.join("~/file.txt"), "literal").unwrap();
+
+        let result = resolve_path(workspace, "~/file.txt");
+        assert_eq!(
+            result.unwrap(),
+            workspace.join("~/file.txt").canonicalize().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_tilde_expands_inside_workspace() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let workspace = temp_dir.path();
+        let home = workspace.join("home");
+        fs::create_dir_all(home.join("project")).unwrap();
+        fs::write(home.join("project/file.txt"), "hello").unwrap();
+        let options = home_options(&home);
+
+        let result = resolve_path_with_options(workspace, "~/project/file.txt", &options);
+        assert_eq!(
+            result.unwrap(),
+            home.join("project/file.txt").canonicalize().unwrap()
+        );
+
+        let result = resolve_path_with_options(workspace, "~", &options);
+        assert_eq!(result.unwrap(), home.canonicalize().unwrap());
+
+        let result = resolve_path_for_write_with_options(workspace, "~/new.txt", &options);
+        assert_eq!(
+            result.unwrap(),
+            home.canonicalize().unwrap().join("new.txt")
+        );
+    }
+
+    #[test]
+    fn test_tilde_home_outside_workspace() {
+        let workspace = tempfile::tempdir().unwrap();
+        let home = tempfile::tempdir().unwrap();
+        fs::write(home.path().join("file.txt"), "secret").unwrap();
+        let options = home_options(home.path());
+
+        let result = resolve_path_with_options(workspace.path(), "~/file.txt", &options);
+        assert!(matches!(result, Err(ToolError::PathOutsideWorkspace(_))));
+
+        let result = resolve_path_for_write_with_options(workspace.path(), "~/new.txt", &options);
+        assert!(matches!(result, Err(ToolError::PathOutsideWorkspace(_))));
+    }
+
+    #[test]
+    fn test_tilde_home_outside_workspace_missing_file() {
+        let workspace = tempfile::tempdir().unwrap();
+        let home = tempfile::tempdir().unwrap();
+        let options = home_options(home.path());
+
+        // Outside, not "not found", and without leaking the expanded home path
+        let result = resolve_path_with_options(workspace.path(), "~/missing.txt", &options);
+        assert!(matches!(result, Err(ToolError::PathOutsideWorkspace(p)) if p == "~/missing.txt"));
+
+        let result = resolve_path_with_options(workspace.path(), "~/a/b/missing.txt", &options);
+        assert!(
+            matches!(result, Err(ToolError::PathOutsideWorkspace(p)) if p == "~/a/b/missing.txt")
+        );
+
+        let result = resolve_path_for_write_with_options(workspace.path(), "~/new.txt", &options);
+        assert!(matches!(result, Err(ToolError::PathOutsideWorkspace(p)) if p == "~/new.txt"));
+    }
+
+    #[test]
+    fn test_tilde_other_user_rejected() {
+        let workspace = tempfile::tempdir().unwrap();
+        let options = home_options(workspace.path());
+
+        let err = resolve_path_with_options(workspace.path(), "~otheruser/file.txt", &options)
+            .unwrap_err();
+        assert!(matches!(err, ToolError::InvalidArgument { .. }));
+        assert!(err.to_string().contains("~otheruser"));
+    }
+
+    #[test]
+    fn test_normalize_windows_path() {
+        assert_eq!(normalize_windows_path(r"\\?\C:\workspace"), r"C:\workspace");
+        assert_eq!(normalize_windows_path(r"c:\workspace\"), r"C:\workspace");
+        assert_eq!(
+            normalize_windows_path("c:/workspace/src"),
+            r"C:\workspace\src"
+        );
+        assert_eq!(
+            normalize_windows_path(r"C:\\workspace\\src"),
+            r"C:\workspace\src"
+        );
+        assert_eq!(normalize_windows_path(r"\\?\c:\"), r"C:\");
+        assert_eq!(
+            normalize_windows_path(r"\\?\UNC\server\share\dir"),
+            r"\\server\share\dir"
+        );
+        assert_eq!(
+            normalize_windows_path(r"\\server\share\dir"),
+            r"\\server\share\dir"
+        );
+    }
+
+    #[test]
+    fn test_windows_path_starts_with() {
+        // Verbatim canonical path vs plain workspace
+        assert!(windows_path_starts_with(
+            r"\\?\C:\workspace\src\main.rs",
+            r"C:\workspace"
+        ));
+        // Drive letter casing and separators
+        assert!(windows_path_starts_with(
+            r"c:/workspace/src/main.rs",
+            r"\\?\C:\workspace\"
+        ));
+        assert!(windows_path_starts_with(r"C:\workspace", r"c:\workspace"));
+        // Component-wise, not string-prefix
+        assert!(!windows_path_starts_with(
+            r"C:\workspace2\file.txt",
+            r"C:\workspace"
+        ));
+        assert!(!windows_path_starts_with(r"D:\workspace", r"C:\workspace"));
+        // UNC vs drive path never match
+        assert!(windows_path_starts_with(
+            r"\\?\UNC\server\share\a",
+            r"\\server\share"
+        ));
+        assert!(!windows_path_starts_with(
+            r"C:\server\share",
+            r"\\server\share"
+        ));
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_windows_mixed_separators_resolve_identically() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let workspace = temp_dir.path();
+        fs::create_dir(workspace.join("src")).unwrap();
+        fs::write(workspace.join("src").join("main.rs"), "fn main() {}").unwrap();
+
+        let backslash = resolve_path(workspace, r"src\main.rs").unwrap();
+        let forward = resolve_path(workspace, "src/main.rs").unwrap();
+        assert_eq!(backslash, forward);
+
+        let backslash = resolve_path_for_write(workspace, r"src\new.rs").unwrap();
+        let forward = resolve_path_for_write(workspace, "src/new.rs").unwrap();
+        assert_eq!(backslash, forward);
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_windows_verbatim_workspace_prefix() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let workspace = temp_dir.path();
+        fs::write(workspace.join("file.txt"), "x").unwrap();
+
+        // A verbatim absolute input and a plain workspace still compare equal
+        let verbatim = workspace.join("file.txt").canonicalize().unwrap();
+        let result = resolve_path(workspace, verbatim.to_str().unwrap());
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_relativize_round_trip() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let workspace = temp_dir.path();
+        fs::create_dir_all(workspace.join("src/nested/deep")).unwrap();
+        fs::write(workspace.join("src/nested/deep/mod.rs"), "").unwrap();
+
+        for path in [
+            "src",
+            "src/nested/deep/mod.rs",
+            "./src/nested/../nested/deep",
+        ] {
+            let resolved = resolve_path(workspace, path).unwrap();
+            let relative = relativize(workspace, &resolved).unwrap();
+            assert_eq!(
+                resolve_path(workspace, relative.to_str().unwrap()).unwrap(),
+                resolved
+            );
+        }
+
+        let resolved = resolve_path(workspace, "src/nested/deep/mod.rs").unwrap();
+        assert_eq!(
+            relativize(workspace, &resolved).unwrap(),
+            Path::new("src/nested/deep/mod.rs")
+        );
+
+        // New files round-trip through the write resolver too
+        let resolved = resolve_path_for_write(workspace, "out/new.txt").unwrap();
+        assert_eq!(
+            relativize(workspace, &resolved).unwrap(),
+            Path::new("out/new.txt")
+        );
+    }
+
+    #[test]
+    fn test_relativize_workspace_root() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let workspace = temp_dir.path();
+
+        let resolved = resolve_path(workspace, ".").unwrap();
+        assert_eq!(relativize(workspace, &resolved).unwrap(), Path::new("."));
+        assert_eq!(relativize(workspace, workspace).unwrap(), Path::new("."));
+    }
+
+    #[test]
+    fn test_relativize_outside() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let workspace = temp_dir.path().join("workspace");
+        fs::create_dir(&workspace).unwrap();
+        fs::write(temp_dir.path().join("sibling.txt"), "").unwrap();
+
+        let result = relativize(&workspace, &temp_dir.path().join("sibling.txt"));
+        assert!(matches!(result, Err(ToolError::PathOutsideWorkspace(_))));
+
+        let result = relativize(&workspace, &workspace.join("../sibling.txt"));
+        assert!(matches!(result, Err(ToolError::PathOutsideWorkspace(_))));
+
+        let result = relativize(&workspace, Path::new("src/main.rs"));
+        assert!(matches!(result, Err(ToolError::PathOutsideWorkspace(_))));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_relativize_aliased_workspace() {
+        // Same shape as macOS, where /var is a symlink to /private/var
+        let temp_dir = tempfile::tempdir().unwrap();
+        let real = temp_dir.path().join("private/var/workspace");
+        let alias = temp_dir.path().join("var");
+        fs::create_dir_all(real.join("src")).unwrap();
+        fs::write(real.join("src/main.rs"), "").unwrap();
+        std::os::unix::fs::symlink(temp_dir.path().join("private/var"), &alias).unwrap();
+        let workspace = alias.join("workspace");
+
+        // Canonical result, un-canonicalized workspace
+        let resolved = resolve_path(&workspace, "src/main.rs").unwrap();
+        assert!(resolved.starts_with(real.canonicalize().unwrap()));
+        assert_eq!(
+            relativize(&workspace, &resolved).unwrap(),
+            Path::new("src/main.rs")
+        );
+
+        // Path spelled through the alias, including one that doesn't exist yet
+        assert_eq!(
+            relativize(&workspace, &workspace.join("src/main.rs")).unwrap(),
+            Path::new("src/main.rs")
+        );
+        assert_eq!(
+            relativize(&workspace, &workspace.join("src/new/file.rs")).unwrap(),
+            Path::new("src/new/file.rs")
+        );
+
+        // A symlink inside the workspace pointing out is not relativized
+        let outside = tempfile::tempdir().unwrap();
+        std::os::unix::fs::symlink(outside.path(), real.join("escape")).unwrap();
+        let result = relativize(&workspace, &workspace.join("escape/file.txt"));
+        assert!(matches!(result, Err(ToolError::PathOutsideWorkspace(_))));
+
+        // Also when spelled under the canonical root, which strips lexically
+        let canonical_root = real.canonicalize().unwrap();
+        let result = relativize(&workspace, &canonical_root.join("escape/file.txt"));
+        assert!(matches!(result, Err(ToolError::PathOutsideWorkspace(_))));
+        let result = relativize(&canonical_root, &canonical_root.join("escape"));
+        assert!(matches!(result, Err(ToolError::PathOutsideWorkspace(_))));
+    }
+
+    #[test]
+    fn test_sandbox_relativize() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let sandbox = Sandbox::new(temp_dir.path()).unwrap();
+        fs::write(temp_dir.path().join("a.txt"), "").unwrap();
+
+        let resolved = sandbox.resolve("a.txt").unwrap();
+        assert_eq!(sandbox.relativize(&resolved).unwrap(), Path::new("a.txt"));
+        assert_eq!(sandbox.relativize(sandbox.root()).unwrap(), Path::new("."));
+    }
+
+    #[test]
+    fn test_resolve_paths_mixed() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let workspace = temp_dir.path();
+        fs::write(workspace.join("exists.txt"), "x").unwrap();
+
+        let results = resolve_paths(workspace, &["exists.txt", "missing.txt", "/etc/passwd"]);
+        assert_eq!(results.len(), 3);
+        assert_eq!(
+            results[0].as_ref().unwrap(),
+            &workspace.join("exists.txt").canonicalize().unwrap()
+        );
+        assert!(matches!(results[1], Err(ToolError::PathNotFound(_))));
+        assert!(matches!(
+            results[2],
+            Err(ToolError::PathOutsideWorkspace(_))
+        ));
+    }
+
+    #[test]
+    fn test_resolve_paths_for_write_mixed() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let workspace = temp_dir.path();
+
+        let results = resolve_paths_for_write(workspace, &["new.txt", "../escape.txt"]);
+        assert!(results[0].is_ok());
+        assert!(matches!(
+            results[1],
+            Err(ToolError::PathOutsideWorkspace(_))
+        ));
+    }
+
+    #[test]
+    fn test_resolve_paths_all_or_nothing() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let workspace = temp_dir.path();
+        fs::write(workspace.join("a.txt"), "a").unwrap();
+        fs::write(workspace.join("b.txt"), "b").unwrap();
+
+        let resolved = resolve_paths_all_or_nothing(workspace, &["a.txt", "b.txt"]).unwrap();
+        assert_eq!(resolved.len(), 2);
+
+        let err = resolve_paths_all_or_nothing(workspace, &["a.txt", "missing.txt", "/etc/passwd"])
+            .unwrap_err();
+        match &err {
+            ToolError::PathsFailed(failures) => {
+                assert_eq!(failures.len(), 2);
+                assert_eq!(failures[0].0, "missing.txt");
+                assert_eq!(failures[0].1, "Path not found: missing.txt");
+                assert_eq!(failures[1].0, "/etc/passwd");
+            }
+            other => panic!("unexpected error: {other:?}"),
+        }
+        assert_eq!(
+            err.to_string(),
+            "2 paths failed:\n  missing.txt: Path not found: missing.txt\n  /etc/passwd: Path '/etc/passwd' is outside workspace"
+        );
+
+        let err =
+            resolve_paths_for_write_all_or_nothing(workspace, &["ok.txt", "../x.txt"]).unwrap_err();
+        assert!(matches!(err, ToolError::PathsFailed(failures) if failures.len() == 1));
+    }
 }
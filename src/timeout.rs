@@ -0,0 +1,206 @@
+//! Timeout validation and enforcement
+//!
+//! Tools accept an optional user-supplied timeout in milliseconds.
+//! [`validate_timeout`] turns it into a [`Duration`] with the same rules
+//! everywhere, and the `with_timeout` helpers enforce it, mapping expiry to
+//! [`ToolError::Timeout`].
+
+use crate::constants::{DEFAULT_TIMEOUT_MS, MAX_TIMEOUT_MS};
+use crate::ToolError;
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+/// Validate a user-supplied timeout
+///
+/// # Arguments
+///
+/// * `requested_ms` - The requested timeout in milliseconds, if any
+///
+/// # Returns
+///
+/// * `Ok(Duration)` - [`DEFAULT_TIMEOUT_MS`] when `None`, otherwise the requested value
+/// * `Err(ToolError::InvalidArgument)` - If the value is 0 or above [`MAX_TIMEOUT_MS`]
+///
+/// # Examples
+///
+/// ```rust
+/// use a3s_tools_core::{validate_timeout, DEFAULT_TIMEOUT_MS, MAX_TIMEOUT_MS};
+/// use std::time::Duration;
+///
+/// assert_eq!(validate_timeout(None).unwrap(), Duration::from_millis(DEFAULT_TIMEOUT_MS));
+/// assert_eq!(validate_timeout(Some(5_000)).unwrap(), Duration::from_secs(5));
+/// assert!(validate_timeout(Some(0)).is_err());
+/// assert!(validate_timeout(Some(MAX_TIMEOUT_MS + 1)).is_err());
+/// ```
+pub fn validate_timeout(requested_ms: Option<u64>) -> Result<Duration, ToolError> {
+    let ms = match requested_ms {
+        None => DEFAULT_TIMEOUT_MS,
+        Some(0) => return Err(ToolError::invalid_arg("timeout", "must be greater than 0")),
+        Some(ms) if ms > MAX_TIMEOUT_MS => {
+            return Err(ToolError::invalid_arg(
+                "timeout",
+                format!("{}ms exceeds the maximum of {}ms", ms, MAX_TIMEOUT_MS),
+            ))
+        }
+        Some(ms) => ms,
+    };
+    Ok(Duration::from_millis(ms))
+}
+
+/// Await `fut`, failing with [`ToolError::Timeout`] if it takes longer than `duration`
+///
+/// Requires the `async` feature and a Tokio runtime with the time driver enabled.
+/// The future is dropped (cancelled) on expiry.
+///
+/// # Examples
+///
+/// ```rust
+/// use a3s_tools_core::{validate_timeout, with_timeout, ToolError};
+/// use std::time::Duration;
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() -> Result<(), ToolError> {
+/// let duration = validate_timeout(Some(50))?;
+///
+/// assert_eq!(with_timeout(duration, async { 42 }).await?, 42);
+///
+/// let slow = tokio::time::sleep(Duration::from_secs(10));
+/// assert!(matches!(with_timeout(duration, slow).await, Err(ToolError::Timeout(50))));
+/// # Ok(())
+/// # }
+/// ```
+#[cfg(feature = "async")]
+pub async fn with_timeout<F: std::future::Future>(
+    duration: Duration,
+    fut: F,
+) -> Result<F::Output, ToolError> {
+    tokio::time::timeout(duration, fut)
+        .await
+        .map_err(|_| ToolError::Timeout(as_millis(duration)))
+}
+
+/// Run a blocking closure on a new thread, failing with [`ToolError::Timeout`]
+/// if it takes longer than `duration`
+///
+/// Useful for blocking file operations (e.g. reading from a hung network mount).
+///
+/// # Returns
+///
+/// * `Ok(T)` - The closure's result
+/// * `Err(ToolError::Timeout)` - If the deadline passed first
+/// * `Err(ToolError::Other)` - If the closure panicked
+///
+/// # Caveats
+///
+/// Threads can't be cancelled, so on timeout the closure keeps running in the
+/// background until it finishes; its result is discarded.
+///
+/// # Examples
+///
+/// ```rust
+/// use a3s_tools_core::{with_timeout_blocking, ToolError};
+/// use std::time::Duration;
+///
+/// let result = with_timeout_blocking(Duration::from_secs(1), || 6 * 7);
+/// assert_eq!(result.unwrap(), 42);
+///
+/// let result = with_timeout_blocking(Duration::from_millis(10), || {
+///     std::thread::sleep(Duration::from_secs(1));
+/// });
+/// assert!(matches!(result, Err(ToolError::Timeout(10))));
+/// ```
+pub fn with_timeout_blocking<F, T>(duration: Duration, f: F) -> Result<T, ToolError>
+where
+    F: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
+{
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        // The receiver is gone if we already timed out
+        let _ = tx.send(f());
+    });
+
+    match rx.recv_timeout(duration) {
+        Ok(value) => Ok(value),
+        Err(mpsc::RecvTimeoutError::Timeout) => Err(ToolError::Timeout(as_millis(duration))),
+        Err(mpsc::RecvTimeoutError::Disconnected) => {
+            Err(ToolError::Other("operation panicked".to_string()))
+        }
+    }
+}
+
+/// Whole milliseconds in `duration`, saturating at `u64::MAX`
+fn as_millis(duration: Duration) -> u64 {
+    u64::try_from(duration.as_millis()).unwrap_or(u64::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_timeout() {
+        assert_eq!(
+            validate_timeout(None).unwrap(),
+            Duration::from_millis(DEFAULT_TIMEOUT_MS)
+        );
+        assert_eq!(validate_timeout(Some(1)).unwrap(), Duration::from_millis(1));
+        assert_eq!(
+            validate_timeout(Some(MAX_TIMEOUT_MS)).unwrap(),
+            Duration::from_millis(MAX_TIMEOUT_MS)
+        );
+
+        assert!(matches!(
+            validate_timeout(Some(0)),
+            Err(ToolError::InvalidArgument { .. })
+        ));
+        let err = validate_timeout(Some(MAX_TIMEOUT_MS + 1)).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Invalid argument 'timeout': 600001ms exceeds the maximum of 600000ms"
+        );
+        assert!(validate_timeout(Some(u64::MAX)).is_err());
+    }
+
+    #[test]
+    fn test_with_timeout_blocking() {
+        let result = with_timeout_blocking(Duration::from_secs(5), || "done");
+        assert_eq!(result.unwrap(), "done");
+
+        let result = with_timeout_blocking(Duration::from_millis(20), || {
+            thread::sleep(Duration::from_millis(500));
+        });
+        assert!(matches!(result, Err(ToolError::Timeout(20))));
+    }
+
+    #[test]
+    fn test_with_timeout_blocking_panic() {
+        let result = with_timeout_blocking(Duration::from_secs(5), || -> u32 {
+            panic!("boom");
+        });
+        assert!(matches!(result, Err(ToolError::Other(_))));
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test(start_paused = true)]
+    async fn test_with_timeout_deadline() {
+        let duration = Duration::from_millis(100);
+
+        // Paused time auto-advances, so these are exact rather than racy
+        let just_before = async {
+            tokio::time::sleep(Duration::from_millis(99)).await;
+            "before"
+        };
+        assert_eq!(with_timeout(duration, just_before).await.unwrap(), "before");
+
+        let just_after = async {
+            tokio::time::sleep(Duration::from_millis(101)).await;
+            "after"
+        };
+        assert!(matches!(
+            with_timeout(duration, just_after).await,
+            Err(ToolError::Timeout(100))
+        ));
+    }
+}